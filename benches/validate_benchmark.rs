@@ -27,7 +27,7 @@ impl MyStruct {
 }
 
 impl Validate for MyStruct {
-    fn validate_inner(&self, accum: &mut validatrix::Accumulator) {
+    fn validate_inner<S: validatrix::FailureSink>(&self, accum: &mut validatrix::Accumulator<S>) {
         if !self.is_valid {
             accum.with_key("is_valid", |a| a.add_failure("not valid"));
         }
@@ -93,5 +93,39 @@ fn validate_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, ser_benchmark, de_benchmark, validate_benchmark);
+/// Benchmark checking validity of the same struct, without building failures.
+fn is_valid_benchmark(c: &mut Criterion) {
+    let s = standard_struct();
+    c.bench_function("is_valid", |b| {
+        b.iter(|| {
+            let _res = black_box(&s).is_valid();
+        })
+    });
+}
+
+/// Benchmark validating many small structs, reusing one accumulator.
+fn validate_reuse_benchmark(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(1991);
+    let structs: Vec<_> = std::iter::repeat_with(|| make_struct(2, 1, 0.9, &mut rng))
+        .take(1000)
+        .collect();
+    c.bench_function("validate_reuse", |b| {
+        let mut accum = validatrix::Accumulator::with_capacity(8);
+        b.iter(|| {
+            for s in black_box(&structs) {
+                s.validate_inner(&mut accum);
+                let _res = accum.take_result();
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    ser_benchmark,
+    de_benchmark,
+    validate_benchmark,
+    is_valid_benchmark,
+    validate_reuse_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,382 @@
+//! Compact storage for very large numbers of failures.
+//!
+//! Each [Failure] owns its path and message, which is convenient for reporting
+//! but costly when pathological inputs produce hundreds of thousands of them.
+//! A [FailureArena] instead copies every failure's keys and message into a few shared buffers,
+//! which are released all at once when the arena is dropped or [cleared](FailureArena::clear).
+use std::{borrow::Cow, fmt::Display, ops::Range};
+
+use crate::{
+    render::{Dotted, PathRenderer},
+    sink::{FailureSink, PendingFailure},
+    span::Span,
+    Accumulator, Checkpoint, Failure, Key, Severity, Validate,
+};
+
+/// Failures stored in shared buffers rather than individually.
+///
+/// Failures' codes, parameters and spans are kept,
+/// but their [payloads](Failure::payload) and [sources](Failure::source) are discarded,
+/// as they cannot be copied back out of the arena.
+///
+/// ```
+/// use validatrix::{arena::FailureArena, Accumulator, FailureSink, Validate};
+///
+/// struct Even(u32);
+///
+/// impl Validate for Even {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 % 2 != 0 {
+///             accum.add_failure(format!("{} is odd", self.0));
+///         }
+///     }
+/// }
+///
+/// let values: Vec<_> = (0..100_000).map(Even).collect();
+/// let mut arena = FailureArena::new();
+/// assert!(!arena.validate(&values));
+/// assert_eq!(arena.len(), 50_000);
+/// assert_eq!(arena.iter().next().unwrap().to_string(), "$[1]: 1 is odd");
+/// ```
+#[derive(Debug, Default)]
+pub struct FailureArena {
+    keys: Vec<Key>,
+    text: String,
+    records: Vec<Record>,
+    n_errors: usize,
+}
+
+#[derive(Debug)]
+struct Record {
+    keys: Range<usize>,
+    message: Message,
+    severity: Severity,
+    code: Option<Cow<'static, str>>,
+    params: Vec<(Cow<'static, str>, String)>,
+    span: Option<Span>,
+}
+
+#[derive(Debug)]
+enum Message {
+    Static(&'static str),
+    Text(Range<usize>),
+}
+
+impl FailureArena {
+    /// Create an empty arena.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty arena with space for `failures` failures before it needs to reallocate.
+    ///
+    /// `keys` and `bytes` are the total number of keys in their paths,
+    /// and bytes in their messages.
+    pub fn with_capacity(failures: usize, keys: usize, bytes: usize) -> Self {
+        Self {
+            keys: Vec::with_capacity(keys),
+            text: String::with_capacity(bytes),
+            records: Vec::with_capacity(failures),
+            n_errors: 0,
+        }
+    }
+
+    /// Validate the value, adding its failures to this arena.
+    ///
+    /// Returns whether the value is valid.
+    pub fn validate<T: Validate + ?Sized>(&mut self, value: &T) -> bool {
+        let n_errors = self.n_errors;
+        let mut accum = Accumulator::with_sink(ArenaSink {
+            base_records: self.records.len(),
+            base_errors: n_errors,
+            arena: self,
+        });
+        value.validate_inner(&mut accum);
+        drop(accum);
+        self.n_errors == n_errors
+    }
+
+    /// Add a failure to the arena.
+    pub fn push(&mut self, failure: Failure) {
+        self.push_at(&[], failure)
+    }
+
+    /// Add a failure whose own keys follow on from the given prefix.
+    fn push_at(&mut self, prefix: &[Key], failure: Failure) {
+        let start = self.keys.len();
+        self.keys.extend_from_slice(prefix);
+        self.keys.extend(failure.key.iter().cloned());
+        let message = match failure.message {
+            Cow::Borrowed(s) => Message::Static(s),
+            Cow::Owned(s) => {
+                let start = self.text.len();
+                self.text.push_str(&s);
+                Message::Text(start..self.text.len())
+            }
+        };
+        if failure.severity == Severity::Error {
+            self.n_errors += 1;
+        }
+        self.records.push(Record {
+            keys: start..self.keys.len(),
+            message,
+            severity: failure.severity,
+            code: failure.code,
+            params: failure.params,
+            span: failure.span,
+        });
+    }
+
+    /// Discard the failures after the first `len`, keeping their keys' and messages' memory.
+    fn truncate(&mut self, len: usize, n_errors: usize) {
+        let Some(first) = self.records.get(len) else {
+            return;
+        };
+        self.keys.truncate(first.keys.start);
+        let text_start = self.records[len..].iter().find_map(|r| match &r.message {
+            Message::Text(range) => Some(range.start),
+            Message::Static(_) => None,
+        });
+        if let Some(start) = text_start {
+            self.text.truncate(start);
+        }
+        self.records.truncate(len);
+        self.n_errors = n_errors;
+    }
+
+    /// Number of failures in the arena.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no failures in the arena.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Whether any failures have [Severity::Error].
+    pub fn has_errors(&self) -> bool {
+        self.n_errors > 0
+    }
+
+    /// Iterate over the failures, in the order they were added.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = ArenaFailure<'_>> + '_ {
+        self.records.iter().map(|r| ArenaFailure {
+            keys: &self.keys[r.keys.clone()],
+            message: match &r.message {
+                Message::Static(s) => s,
+                Message::Text(range) => &self.text[range.clone()],
+            },
+            severity: r.severity,
+            code: r.code.as_deref(),
+            params: &r.params,
+            span: r.span,
+        })
+    }
+
+    /// Discard all failures, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.text.clear();
+        self.records.clear();
+        self.n_errors = 0;
+    }
+
+    /// Build a [Result](crate::Result) from the failures, allocating each individually.
+    ///
+    /// Like an [Accumulator], this is only an error if any failures have [Severity::Error].
+    pub fn to_result(&self) -> crate::Result {
+        if !self.has_errors() {
+            return Ok(());
+        }
+        Err(self.iter().map(|f| f.to_failure()).collect())
+    }
+}
+
+/// A failure borrowed from a [FailureArena].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaFailure<'a> {
+    keys: &'a [Key],
+    message: &'a str,
+    severity: Severity,
+    code: Option<&'a str>,
+    params: &'a [(Cow<'static, str>, String)],
+    span: Option<Span>,
+}
+
+impl<'a> ArenaFailure<'a> {
+    /// Path to this failure.
+    pub fn keys(&self) -> &'a [Key] {
+        self.keys
+    }
+
+    /// Description of this failure.
+    pub fn message(&self) -> &'a str {
+        self.message
+    }
+
+    /// How serious this failure is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Machine-readable code for this failure, if any.
+    pub fn code(&self) -> Option<&'a str> {
+        self.code
+    }
+
+    /// The named parameters of this failure, in the order they were added.
+    pub fn params(&self) -> impl Iterator<Item = (&'a str, &'a str)> + 'a {
+        self.params.iter().map(|(k, v)| (k.as_ref(), v.as_str()))
+    }
+
+    /// The location of the failing value in the source text, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Copy this failure out of the arena.
+    pub fn to_failure(&self) -> Failure {
+        let mut failure =
+            Failure::new(self.keys, self.message.to_owned()).with_severity(self.severity);
+        failure.code = self.code.map(|c| Cow::Owned(c.to_owned()));
+        failure.params = self.params.to_vec();
+        failure.span = self.span;
+        failure
+    }
+}
+
+/// Formatted like [Failure].
+impl Display for ArenaFailure<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Dotted.render(self.keys, f)?;
+        if self.severity == Severity::Warning {
+            f.write_str(" (warning)")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Copies failures into an arena as soon as they are reported.
+struct ArenaSink<'a> {
+    arena: &'a mut FailureArena,
+    /// Number of failures in the arena before validation started.
+    base_records: usize,
+    /// Number of errors in the arena before validation started.
+    base_errors: usize,
+}
+
+impl FailureSink for ArenaSink<'_> {
+    fn record(&mut self, failure: PendingFailure<'_>) {
+        let (prefix, failure) = failure.into_relative();
+        self.arena.push_at(prefix, failure)
+    }
+
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        self.arena.truncate(
+            self.base_records + checkpoint.failures(),
+            self.base_errors + checkpoint.errors(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Item(u32);
+
+    impl Validate for Item {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if self.0 % 3 == 0 {
+                accum.add_failure_coded("multiple", "is a multiple of 3");
+            }
+            if self.0 % 5 == 0 {
+                accum.add_warning(format!("{} is a multiple of 5", self.0));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_error() {
+        let items: Vec<_> = (1..20).map(Item).collect();
+        let mut arena = FailureArena::new();
+        assert!(!arena.validate(&items));
+        let expected = items.validate().unwrap_err();
+        assert_eq!(arena.len(), expected.len());
+        for (stored, failure) in arena.iter().zip(expected.failures()) {
+            assert_eq!(stored.to_string(), failure.to_string());
+            assert_eq!(stored.code(), failure.code());
+        }
+        assert_eq!(
+            arena.to_result().unwrap_err().to_string(),
+            expected.to_string()
+        );
+    }
+
+    #[test]
+    fn accumulates() {
+        let mut arena = FailureArena::with_capacity(4, 4, 64);
+        assert!(arena.validate(&Item(5)));
+        assert!(!arena.has_errors());
+        assert!(arena.to_result().is_ok());
+        assert!(!arena.validate(&Item(3)));
+        assert_eq!(arena.len(), 2);
+        arena.clear();
+        assert!(arena.is_empty());
+    }
+
+    struct Either(&'static str);
+
+    impl Validate for Either {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            accum.add_warning_at("raw", "iffy");
+            let cp = accum.checkpoint();
+            if self.0.parse::<u32>().is_err() {
+                accum.add_failure_at("number", format!("{:?} is not a number", self.0));
+            }
+            if accum.errors_since(&cp) > 0 {
+                accum.rollback_to(cp);
+                if !self.0.starts_with('$') {
+                    accum.add_failure_at("variable", "not a variable");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rollback() {
+        let mut arena = FailureArena::new();
+        assert!(!arena.validate(&Either("x")));
+        assert!(arena.validate(&Either("$x")));
+        assert!(arena.validate(&Either("3")));
+        let rendered: Vec<_> = arena.iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            rendered,
+            [
+                "$.raw (warning): iffy",
+                "$.variable: not a variable",
+                "$.raw (warning): iffy",
+                "$.raw (warning): iffy",
+            ]
+        );
+        assert_eq!(arena.to_result().unwrap_err().len(), 4);
+    }
+
+    #[test]
+    fn keeps_params_and_span() {
+        let span = Span::from_offset("a\nbc", 3);
+        let mut arena = FailureArena::new();
+        arena.push(
+            Failure::new(&["a".into()], "bad")
+                .with_param("max", 3)
+                .with_span(span),
+        );
+        let stored = arena.iter().next().unwrap();
+        assert_eq!(stored.params().collect::<Vec<_>>(), [("max", "3")]);
+        assert_eq!(stored.span(), Some(span));
+        let failure = stored.to_failure();
+        assert_eq!(failure.param("max"), Some("3"));
+        assert_eq!(failure.span(), Some(span));
+    }
+}
@@ -1,4 +1,29 @@
-use crate::errors::Accumulator;
+use alloc::{boxed::Box, format, string::String, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use crate::{errors::Accumulator, FailureSink, Key};
+
+#[cfg(feature = "std")]
+pub mod rt;
+#[cfg(feature = "stream")]
+mod stream;
+#[cfg(feature = "stream")]
+pub use stream::{validate_stream, validate_stream_all, StreamValidateExt};
+
+/// A boxed future which can be sent between threads, as returned by [DynValidate].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 
 /// Trait for asynchronous validation.
 #[allow(async_fn_in_trait)]
@@ -9,37 +34,1226 @@ pub trait Validate {
     /// use [Validate::validate_inner] instead.
     /// Should not be overridden by implementors.
     async fn validate(&self) -> crate::Result {
-        let mut accum = Accumulator::new();
+        crate::telemetry::timed_async::<Self>(async {
+            let mut accum = Accumulator::new();
+            self.validate_inner(&mut accum).await;
+            accum
+        })
+        .await
+    }
+
+    /// Check whether this value is valid, without reporting why not.
+    ///
+    /// See [crate::synch::Validate::is_valid].
+    /// Should not be overridden by implementors.
+    async fn is_valid(&self) -> bool {
+        let mut accum = Accumulator::validity();
         self.validate_inner(&mut accum).await;
-        accum.into()
+        !accum.has_errors()
     }
 
     /// Accumulate validation errors.
     ///
     /// Validators of containing types should call this;
     /// end users probably want [Validate::validate] instead.
-    async fn validate_inner(&self, accum: &mut Accumulator);
+    async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>);
+}
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Like [Accumulator::validate_iter], for async validators,
+    /// validating up to `concurrency` items at a time (or all at once, if `None`).
+    ///
+    /// Each item is validated into a separate accumulator,
+    /// and their failures are added to this one in the order of the items.
+    ///
+    /// ```
+    /// use validatrix::{asynch::Validate, Accumulator, FailureSink};
+    ///
+    /// struct UserId(u32);
+    ///
+    /// impl Validate for UserId {
+    ///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         // e.g. look up the user in a database
+    ///         if self.0 == 0 {
+    ///             accum.add_failure("no such user");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// struct Team(Vec<UserId>);
+    ///
+    /// impl Validate for Team {
+    ///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.validate_iter_concurrent(&self.0, Some(10)).await;
+    ///     }
+    /// }
+    ///
+    /// let team = Team(vec![UserId(1), UserId(0)]);
+    /// let err = pollster::block_on(team.validate()).unwrap_err();
+    /// assert_eq!(err.to_string(), "Validation failure(s):\n   $[1]: no such user");
+    /// ```
+    pub async fn validate_iter_concurrent<'a, V, I>(&mut self, items: I, concurrency: Option<usize>)
+    where
+        V: Validate + ?Sized + 'a,
+        I: IntoIterator<Item = &'a V>,
+    {
+        if self.is_full() {
+            return;
+        }
+        if self.at_max_depth() {
+            for (idx, _) in items.into_iter().enumerate() {
+                if self.is_full() {
+                    return;
+                }
+                self.add_failure_at(idx, "maximum validation depth exceeded");
+            }
+            return;
+        }
+        let this = &*self;
+        let futures = items.into_iter().map(|item| {
+            let mut sub = this.fork();
+            async move {
+                item.validate_inner(&mut sub).await;
+                sub
+            }
+        });
+        let subs = join_limited(futures, concurrency).await;
+        for (idx, sub) in subs.into_iter().enumerate() {
+            self.merge_at(idx, sub);
+        }
+    }
+}
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Validate an async member at the given key,
+    /// adding a failure there instead if the `timeout` future completes first.
+    ///
+    /// Any failures the member added before timing out are discarded.
+    /// As there is no runtime-agnostic timer, `timeout` is typically your runtime's sleep future,
+    /// e.g. `tokio::time::sleep(Duration::from_secs(1))`.
+    /// See also [Accumulator::validate_member_timeout] for runtimes implementing [rt::Runtime].
+    ///
+    /// ```
+    /// use std::future::pending;
+    /// use validatrix::{asynch::Validate, Accumulator, FailureSink};
+    ///
+    /// struct Stalls;
+    ///
+    /// impl Validate for Stalls {
+    ///     async fn validate_inner<S: FailureSink>(&self, _accum: &mut Accumulator<S>) {
+    ///         pending::<()>().await
+    ///     }
+    /// }
+    ///
+    /// let mut accum = Accumulator::new();
+    /// // a timer which has already expired
+    /// pollster::block_on(accum.validate_member_with_timeout_future("lookup", &Stalls, async {}));
+    /// assert_eq!(
+    ///     validatrix::Result::from(accum).unwrap_err().to_string(),
+    ///     "Validation failure(s):\n   $.lookup: validation timed out",
+    /// );
+    /// ```
+    pub async fn validate_member_with_timeout_future<V: Validate + ?Sized>(
+        &mut self,
+        field: impl Into<Key>,
+        member: &V,
+        timeout: impl Future<Output = ()>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        let field = field.into();
+        if self.at_max_depth() {
+            self.add_failure_at(field, "maximum validation depth exceeded");
+            return;
+        }
+        let mut sub = self.fork();
+        let validation = member.validate_inner(&mut sub);
+        match first(validation, timeout).await {
+            Either::Left(()) => self.merge_at(field, sub),
+            Either::Right(()) => self.add_failure_at(field, "validation timed out"),
+        }
+    }
+}
+
+/// Validate the value, stopping early if `cancel` completes first.
+///
+/// `cancel` could be e.g. `tokio_util`'s `CancellationToken::cancelled()`,
+/// or a future which completes when the client disconnects.
+/// Validation stops at the next `.await` point once `cancel` completes.
+/// Note that dropping any validation future also cancels it; this function is only needed
+/// to distinguish cancellation from completion, or to keep the partial results.
+///
+/// ```
+/// use std::future::pending;
+/// use validatrix::{asynch::{validate_cancellable, Validate}, Accumulator, FailureSink};
+///
+/// struct Stalls;
+///
+/// impl Validate for Stalls {
+///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         accum.add_failure("checked before stalling");
+///         pending::<()>().await
+///     }
+/// }
+///
+/// let cancelled = pollster::block_on(validate_cancellable(&Stalls, async {})).unwrap_err();
+/// assert_eq!(cancelled.partial().unwrap().len(), 1);
+/// ```
+pub async fn validate_cancellable<V: Validate + ?Sized>(
+    value: &V,
+    cancel: impl Future<Output = ()>,
+) -> Result<crate::Result, Cancelled> {
+    let mut accum = Accumulator::new();
+    match first(value.validate_inner(&mut accum), cancel).await {
+        Either::Left(()) => Ok(accum.into()),
+        Either::Right(()) => Err(Cancelled {
+            partial: accum.take_result().err(),
+        }),
+    }
+}
+
+/// Validation was cancelled before it completed; see [validate_cancellable].
+#[derive(Debug)]
+pub struct Cancelled {
+    partial: Option<crate::Error>,
+}
+
+impl Cancelled {
+    /// Failures found before validation was cancelled, if there were any errors.
+    ///
+    /// The value may have further failures which were not found.
+    pub fn partial(&self) -> Option<&crate::Error> {
+        self.partial.as_ref()
+    }
+
+    /// Consume this, returning the failures found before validation was cancelled.
+    pub fn into_partial(self) -> Option<crate::Error> {
+        self.partial
+    }
+}
+
+impl core::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("validation was cancelled")
+    }
+}
+
+impl crate::errors::StdError for Cancelled {}
+
+/// [Code](crate::Failure::code) of failures added when a check could not be completed
+/// within a [RetryPolicy], as opposed to the value being invalid.
+pub const RETRIES_EXHAUSTED: &str = "retries_exhausted";
+
+/// How to retry async checks which fail for reasons unrelated to the value being validated,
+/// e.g. network errors; see [Accumulator::retry].
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy<B = fn(u32) -> core::future::Ready<()>> {
+    max_attempts: u32,
+    backoff: B,
+}
+
+impl RetryPolicy {
+    /// Make up to `max_attempts` attempts (at least 1), with no delay between them.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff: |_| core::future::ready(()),
+        }
+    }
+}
+
+impl<B> RetryPolicy<B> {
+    /// Wait between attempts by awaiting the future returned by `backoff`,
+    /// which is given the number of attempts made so far.
+    ///
+    /// As there is no runtime-agnostic timer, this is typically your runtime's sleep future,
+    /// e.g. `|n| tokio::time::sleep(Duration::from_millis(100 << n))` for exponential backoff,
+    /// or `move |n| runtime.sleep(Duration::from_millis(100 << n))` for any [rt::Runtime].
+    pub fn with_backoff<B2, Fut>(self, backoff: B2) -> RetryPolicy<B2>
+    where
+        B2: Fn(u32) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            backoff,
+        }
+    }
+
+    async fn run<T, E, F, Fut, BFut>(&self, mut check: F) -> Result<T, (u32, E)>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        B: Fn(u32) -> BFut,
+        BFut: Future<Output = ()>,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match check().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempts >= self.max_attempts => return Err((attempts, e)),
+                Err(_) => (self.backoff)(attempts).await,
+            }
+        }
+    }
+}
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Run an async check which may fail for transient reasons, retrying according to the policy.
+    ///
+    /// `check` should return `Err` only for failures unrelated to the value (e.g. network errors),
+    /// and `Ok` with whatever is needed to validate the value otherwise.
+    /// If every attempt fails, `None` is returned and a failure with the code [RETRIES_EXHAUSTED] is added,
+    /// so that it can be distinguished from the value being invalid.
+    ///
+    /// ```
+    /// use validatrix::{asynch::{RetryPolicy, Validate, RETRIES_EXHAUSTED}, Accumulator, FailureSink};
+    ///
+    /// struct Username(String);
+    ///
+    /// impl Validate for Username {
+    ///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         let policy = RetryPolicy::new(3);
+    ///         let exists = accum
+    ///             .retry(&policy, || async { Err::<bool, _>("connection refused") })
+    ///             .await;
+    ///         if exists == Some(true) {
+    ///             accum.add_failure("is taken");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let err = pollster::block_on(Username("alice".into()).validate()).unwrap_err();
+    /// let failure = err.errors().next().unwrap();
+    /// assert_eq!(failure.code(), Some(RETRIES_EXHAUSTED));
+    /// assert_eq!(failure.message(), "could not be checked after 3 attempt(s): connection refused");
+    /// ```
+    pub async fn retry<T, E, F, Fut, B, BFut>(
+        &mut self,
+        policy: &RetryPolicy<B>,
+        check: F,
+    ) -> Option<T>
+    where
+        E: core::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        B: Fn(u32) -> BFut,
+        BFut: Future<Output = ()>,
+    {
+        match policy.run(check).await {
+            Ok(value) => Some(value),
+            Err((attempts, e)) => {
+                self.add_failure_coded(RETRIES_EXHAUSTED, retries_exhausted(attempts, e));
+                None
+            }
+        }
+    }
+
+    /// Like [Self::retry], adding any failure at the given key.
+    pub async fn retry_at<T, E, F, Fut, B, BFut>(
+        &mut self,
+        field: impl Into<Key>,
+        policy: &RetryPolicy<B>,
+        check: F,
+    ) -> Option<T>
+    where
+        E: core::fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        B: Fn(u32) -> BFut,
+        BFut: Future<Output = ()>,
+    {
+        match policy.run(check).await {
+            Ok(value) => Some(value),
+            Err((attempts, e)) => {
+                self.add_failure_coded_at(field, RETRIES_EXHAUSTED, retries_exhausted(attempts, e));
+                None
+            }
+        }
+    }
+}
+
+fn retries_exhausted(attempts: u32, error: impl core::fmt::Display) -> String {
+    format!("could not be checked after {attempts} attempt(s): {error}")
+}
+
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// Poll both futures until one completes, preferring the left.
+async fn first<L: Future, R: Future>(left: L, right: R) -> Either<L::Output, R::Output> {
+    let mut left = core::pin::pin!(left);
+    let mut right = core::pin::pin!(right);
+    core::future::poll_fn(|cx| {
+        if let Poll::Ready(out) = left.as_mut().poll(cx) {
+            return Poll::Ready(Either::Left(out));
+        }
+        right.as_mut().poll(cx).map(Either::Right)
+    })
+    .await
+}
+
+/// Used by [join_members](crate::join_members):
+/// validate a member in a forked accumulator, unless the member should not be validated.
+#[doc(hidden)]
+pub async fn __validate_forked<V: Validate + ?Sized, S: FailureSink>(
+    accum: &Accumulator<S>,
+    key: impl Into<Key>,
+    member: &V,
+) -> (Key, Option<Accumulator>) {
+    let key = key.into();
+    if accum.is_full() || accum.at_max_depth() {
+        return (key, None);
+    }
+    let mut sub = accum.fork();
+    // the future is created here so that it does not borrow `accum`
+    let fut = async move {
+        member.validate_inner(&mut sub).await;
+        sub
+    };
+    (key, Some(fut.await))
+}
+
+/// Used by [join_members](crate::join_members): poll both futures concurrently.
+#[doc(hidden)]
+pub async fn __join_front<T>(
+    first: impl Future<Output = T>,
+    rest: impl Future<Output = Vec<T>>,
+) -> Vec<T> {
+    let mut first = core::pin::pin!(first);
+    let mut rest = core::pin::pin!(rest);
+    let mut first_out = None;
+    let mut rest_out = None;
+    core::future::poll_fn(|cx| {
+        if first_out.is_none() {
+            if let Poll::Ready(out) = first.as_mut().poll(cx) {
+                first_out = Some(out);
+            }
+        }
+        if rest_out.is_none() {
+            if let Poll::Ready(out) = rest.as_mut().poll(cx) {
+                rest_out = Some(out);
+            }
+        }
+        if first_out.is_some() && rest_out.is_some() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    let mut out = rest_out.expect("future has completed");
+    out.insert(0, first_out.expect("future has completed"));
+    out
+}
+
+/// Used by [join_members](crate::join_members): merge the members' failures in order.
+#[doc(hidden)]
+pub fn __merge_members<S: FailureSink>(
+    accum: &mut Accumulator<S>,
+    members: Vec<(Key, Option<Accumulator>)>,
+) {
+    for (key, sub) in members {
+        match sub {
+            Some(sub) => accum.merge_at(key, sub),
+            None if accum.is_full() => return,
+            None => accum.add_failure_at(key, "maximum validation depth exceeded"),
+        }
+    }
+}
+
+/// Waker given to one of the futures in [join_limited],
+/// which marks that future as ready to be polled again before waking the task.
+struct MemberWaker {
+    woken: AtomicBool,
+    task: Waker,
+}
+
+impl MemberWaker {
+    /// A waker which starts off woken, so that the future is polled at least once.
+    fn new(task: &Waker) -> Arc<Self> {
+        Arc::new(Self {
+            woken: AtomicBool::new(true),
+            task: task.clone(),
+        })
+    }
+}
+
+impl Wake for MemberWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::Release);
+        self.task.wake_by_ref();
+    }
+}
+
+/// Poll the futures concurrently, with at most `limit` in progress at once,
+/// returning their outputs in order.
+///
+/// Each future has its own waker, so that only those which have been woken are polled again.
+async fn join_limited<F: Future>(
+    futures: impl IntoIterator<Item = F>,
+    limit: Option<usize>,
+) -> Vec<F::Output> {
+    let limit = limit.unwrap_or(usize::MAX).max(1);
+    let mut pending = futures.into_iter().peekable();
+    let mut running: Vec<(usize, Pin<Box<F>>, Arc<MemberWaker>)> = Vec::new();
+    let mut outputs: Vec<Option<F::Output>> = Vec::new();
+    let mut task: Option<Waker> = None;
+    core::future::poll_fn(|cx: &mut Context<'_>| loop {
+        if !task.as_ref().is_some_and(|t| t.will_wake(cx.waker())) {
+            // the task's waker changed, so wakers given out earlier may wake the wrong task
+            task = Some(cx.waker().clone());
+            for (_, _, waker) in running.iter_mut() {
+                *waker = MemberWaker::new(cx.waker());
+            }
+        }
+        while running.len() < limit {
+            let Some(future) = pending.next() else {
+                break;
+            };
+            running.push((
+                outputs.len(),
+                Box::pin(future),
+                MemberWaker::new(cx.waker()),
+            ));
+            outputs.push(None);
+        }
+        let n_running = running.len();
+        running.retain_mut(|(idx, future, waker)| {
+            if !waker.woken.swap(false, Ordering::AcqRel) {
+                return true;
+            }
+            let waker = Waker::from(Arc::clone(waker));
+            match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                Poll::Ready(output) => {
+                    outputs[*idx] = Some(output);
+                    false
+                }
+                Poll::Pending => true,
+            }
+        });
+        if running.is_empty() && pending.peek().is_none() {
+            return Poll::Ready(());
+        }
+        if running.len() == n_running {
+            return Poll::Pending;
+        }
+    })
+    .await;
+    outputs
+        .into_iter()
+        .map(|output| output.expect("all futures have completed"))
+        .collect()
+}
+
+/// Every synchronously-validated type can also be validated asynchronously,
+/// so async validators can use them as members.
+///
+/// ```
+/// use validatrix::{asynch, Accumulator, FailureSink, Validate};
+///
+/// struct Port(u16);
+///
+/// impl Validate for Port {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 < 1024 {
+///             accum.add_failure("must not be privileged");
+///         }
+///     }
+/// }
+///
+/// assert!(pollster::block_on(asynch::Validate::validate(&Port(80))).is_err());
+/// ```
+impl<T: crate::Validate + ?Sized> Validate for T {
+    async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        crate::Validate::validate_inner(self, accum)
+    }
 }
 
 /// Trait for asynchronous validation where some external data or context is required.
+///
+/// The context type is generic over a lifetime,
+/// so it can borrow resources (connection pools, request data etc.)
+/// which are passed down through nested validators.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use validatrix::{asynch::ValidateContext, Accumulator, FailureSink};
+///
+/// struct Request<'a> {
+///     known_users: &'a HashSet<u32>,
+///     caller: u32,
+/// }
+///
+/// struct Mention(u32);
+///
+/// impl ValidateContext for Mention {
+///     type Context<'c> = Request<'c>;
+///
+///     async fn validate_inner_ctx<S: FailureSink>(&self, context: &Self::Context<'_>, accum: &mut Accumulator<S>) {
+///         // e.g. query a database through a borrowed pool
+///         if !context.known_users.contains(&self.0) {
+///             accum.add_failure("no such user");
+///         } else if self.0 == context.caller {
+///             accum.add_failure("cannot mention yourself");
+///         }
+///     }
+/// }
+///
+/// struct Post {
+///     mention: Mention,
+/// }
+///
+/// impl ValidateContext for Post {
+///     type Context<'c> = Request<'c>;
+///
+///     async fn validate_inner_ctx<S: FailureSink>(&self, context: &Self::Context<'_>, accum: &mut Accumulator<S>) {
+///         accum
+///             .validate_member_at_ctx_async("mention", &self.mention, context)
+///             .await;
+///     }
+/// }
+///
+/// let known_users = HashSet::from([1, 2]);
+/// let request = Request { known_users: &known_users, caller: 1 };
+/// let post = Post { mention: Mention(1) };
+/// let err = pollster::block_on(post.validate_ctx(&request)).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Validation failure(s):\n   $.mention: cannot mention yourself",
+/// );
+/// ```
 #[allow(async_fn_in_trait)]
 pub trait ValidateContext {
-    /// Type of context which the validator needs (external data, resources etc.)
-    type Context;
+    /// Type of context which the validator needs (external data, resources etc.),
+    /// which may borrow data for the lifetime `'c`.
+    type Context<'c>;
 
     /// Perform top-level validation on this value, with the given context.
     ///
     /// Should not be called inside other validators;
-    /// use [ValidateContext::validate_inner] instead.
-    async fn validate_ctx(&self, context: &Self::Context) -> crate::Result {
-        let mut accum = Accumulator::new();
+    /// use [ValidateContext::validate_inner_ctx] instead.
+    async fn validate_ctx(&self, context: &Self::Context<'_>) -> crate::Result {
+        crate::telemetry::timed_async::<Self>(async {
+            let mut accum = Accumulator::new();
+            self.validate_inner_ctx(context, &mut accum).await;
+            accum
+        })
+        .await
+    }
+
+    /// Like [Validate::is_valid], with the given context.
+    ///
+    /// Should not be overridden by implementors.
+    async fn is_valid_ctx(&self, context: &Self::Context<'_>) -> bool {
+        let mut accum = Accumulator::validity();
         self.validate_inner_ctx(context, &mut accum).await;
-        accum.into()
+        !accum.has_errors()
     }
 
     /// Accumulate validation errors.
     ///
     /// Validators of containing types should call this;
-    /// end users probably want [ValidateContext::validate] instead.
-    async fn validate_inner_ctx(&self, context: &Self::Context, accum: &mut Accumulator);
+    /// end users probably want [ValidateContext::validate_ctx] instead.
+    async fn validate_inner_ctx<S: FailureSink>(
+        &self,
+        context: &Self::Context<'_>,
+        accum: &mut Accumulator<S>,
+    );
+}
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Like [Accumulator::validate_member_at_ctx], for async validators.
+    ///
+    /// The `_async` suffix (as in [Valid::try_new_ctx_async](crate::Valid::try_new_ctx_async))
+    /// distinguishes it from the synchronous method of the same name.
+    pub async fn validate_member_at_ctx_async<V: ValidateContext + ?Sized>(
+        &mut self,
+        field: impl Into<Key>,
+        member: &V,
+        context: &V::Context<'_>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        let field = field.into();
+        if self.at_max_depth() {
+            self.add_failure_at(field, "maximum validation depth exceeded");
+            return;
+        }
+        let mut sub = self.fork();
+        member.validate_inner_ctx(context, &mut sub).await;
+        self.merge_at(field, sub);
+    }
+}
+
+impl<T: Validate> ValidateContext for crate::synch::NoContext<T> {
+    type Context<'c> = ();
+
+    async fn validate_inner_ctx<S: FailureSink>(
+        &self,
+        _context: &Self::Context<'_>,
+        accum: &mut Accumulator<S>,
+    ) {
+        self.0.validate_inner(accum).await
+    }
+}
+
+/// Object-safe variant of [Validate] whose futures are [Send],
+/// for use with trait objects and multi-threaded runtimes.
+///
+/// The futures returned by [Validate]'s methods cannot be named,
+/// so can neither be used through `dyn Validate` nor be required to be `Send` in generic code.
+/// This trait boxes them instead.
+/// Types which already implement [Validate] can implement this with [impl_dyn_validate](crate::impl_dyn_validate),
+/// which checks that their futures are `Send`.
+/// `dyn DynValidate` implements [Validate].
+///
+/// ```
+/// use validatrix::{
+///     asynch::{BoxFuture, DynValidate, Validate},
+///     Accumulator,
+/// };
+///
+/// struct Username(String);
+///
+/// impl DynValidate for Username {
+///     fn validate_inner_boxed<'a>(&'a self, accum: &'a mut Accumulator) -> BoxFuture<'a, ()> {
+///         Box::pin(async move {
+///             if self.0.is_empty() {
+///                 accum.add_failure("must not be empty");
+///             }
+///         })
+///     }
+/// }
+///
+/// fn assert_send<T: Send>(value: T) -> T {
+///     value
+/// }
+///
+/// let items: Vec<Box<dyn DynValidate>> = vec![Box::new(Username("".into()))];
+/// let fut = assert_send(async move { items[0].validate_boxed().await });
+/// assert!(pollster::block_on(fut).is_err());
+/// ```
+pub trait DynValidate: Send + Sync {
+    /// Like [Validate::validate].
+    ///
+    /// Should not be overridden by implementors.
+    fn validate_boxed(&self) -> BoxFuture<'_, crate::Result> {
+        Box::pin(crate::telemetry::timed_async::<Self>(async move {
+            let mut accum = Accumulator::new();
+            self.validate_inner_boxed(&mut accum).await;
+            accum
+        }))
+    }
+
+    /// Like [Validate::validate_inner].
+    fn validate_inner_boxed<'a>(&'a self, accum: &'a mut Accumulator) -> BoxFuture<'a, ()>;
+}
+
+impl Validate for dyn DynValidate + '_ {
+    async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        let mut fork = accum.fork();
+        self.validate_inner_boxed(&mut fork).await;
+        accum.merge(fork);
+    }
+}
+
+/// An asynchronous lookup of values by key, e.g. from a database; see [CachedContext].
+#[allow(async_fn_in_trait)]
+pub trait Lookup<K> {
+    /// Type of the values looked up.
+    type Value;
+
+    /// Look up the value for the key.
+    async fn lookup(&self, key: &K) -> Self::Value;
+}
+
+impl<K, V, F, Fut> Lookup<K> for F
+where
+    F: Fn(&K) -> Fut,
+    Fut: Future<Output = V>,
+{
+    type Value = V;
+
+    async fn lookup(&self, key: &K) -> V {
+        self(key).await
+    }
+}
+
+/// Caches the results of a [Lookup], for use as a [ValidateContext::Context]
+/// when many values refer to the same few keys.
+///
+/// Concurrent requests for a key which is being looked up wait for that lookup,
+/// so each key is looked up once however many values are validated concurrently.
+/// Values are cached until they are older than the TTL, if any, or are invalidated.
+///
+/// ```
+/// use std::{collections::HashSet, sync::atomic::{AtomicUsize, Ordering}};
+/// use validatrix::{asynch::{CachedContext, Lookup, Validate, ValidateContext}, Accumulator, FailureSink};
+///
+/// struct Db {
+///     queries: AtomicUsize,
+/// }
+///
+/// impl Lookup<u32> for Db {
+///     type Value = bool;
+///
+///     async fn lookup(&self, product: &u32) -> bool {
+///         self.queries.fetch_add(1, Ordering::Relaxed);
+///         *product < 100
+///     }
+/// }
+///
+/// struct Row {
+///     product: u32,
+/// }
+///
+/// impl ValidateContext for Row {
+///     type Context<'c> = CachedContext<u32, Db>;
+///
+///     async fn validate_inner_ctx<S: FailureSink>(&self, products: &Self::Context<'_>, accum: &mut Accumulator<S>) {
+///         if !products.get(&self.product).await {
+///             accum.add_failure_at("product", "does not exist");
+///         }
+///     }
+/// }
+///
+/// let products = CachedContext::new(Db { queries: AtomicUsize::new(0) });
+/// let rows: Vec<_> = (0..500).map(|i| Row { product: i % 3 }).collect();
+/// pollster::block_on(async {
+///     for row in &rows {
+///         row.validate_ctx(&products).await.unwrap();
+///     }
+/// });
+/// assert_eq!(products.inner().queries.load(Ordering::Relaxed), 3);
+/// ```
+#[cfg(feature = "std")]
+pub struct CachedContext<K, L: Lookup<K>> {
+    inner: L,
+    ttl: Option<Duration>,
+    cache: Mutex<HashMap<K, Entry<L::Value>>>,
+}
+
+#[cfg(feature = "std")]
+enum Entry<V> {
+    Ready(Instant, V),
+    /// Wakers of tasks waiting for the lookup in progress.
+    Loading(Vec<Waker>),
+}
+
+#[cfg(feature = "std")]
+impl<K, L: Lookup<K>> CachedContext<K, L> {
+    /// Cache the results of the given lookup, with no TTL.
+    pub fn new(inner: L) -> Self {
+        Self {
+            inner,
+            ttl: None,
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Look values up again once they are older than this.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Borrow the underlying lookup.
+    pub fn inner(&self) -> &L {
+        &self.inner
+    }
+
+    /// Discard all cached values.
+    pub fn clear(&self) {
+        self.lock()
+            .retain(|_, entry| matches!(entry, Entry::Loading(_)));
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<K, Entry<L::Value>>> {
+        // the map is always left in a consistent state
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash + Clone, L: Lookup<K>> CachedContext<K, L>
+where
+    L::Value: Clone,
+{
+    /// Get the value for the key, looking it up if it is not cached.
+    pub async fn get(&self, key: &K) -> L::Value {
+        loop {
+            let wait = {
+                let mut cache = self.lock();
+                match cache.get(key) {
+                    Some(Entry::Ready(at, value))
+                        if self.ttl.map_or(true, |ttl| at.elapsed() < ttl) =>
+                    {
+                        return value.clone();
+                    }
+                    Some(Entry::Loading(_)) => true,
+                    _ => {
+                        cache.insert(key.clone(), Entry::Loading(Vec::new()));
+                        false
+                    }
+                }
+            };
+            if wait {
+                core::future::poll_fn(|cx| match self.lock().get_mut(key) {
+                    Some(Entry::Loading(wakers)) => {
+                        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                            wakers.push(cx.waker().clone());
+                        }
+                        Poll::Pending
+                    }
+                    _ => Poll::Ready(()),
+                })
+                .await;
+                continue;
+            }
+            let mut guard = LoadGuard {
+                context: self,
+                key,
+                value: None,
+            };
+            let value = self.inner.lookup(key).await;
+            guard.value = Some(value.clone());
+            return value;
+        }
+    }
+
+    /// Discard the cached value for the key, if any.
+    pub fn invalidate(&self, key: &K) {
+        let mut cache = self.lock();
+        if let Some(Entry::Ready(..)) = cache.get(key) {
+            cache.remove(key);
+        }
+    }
+}
+
+/// Stores the looked-up value (or, if the lookup was cancelled, removes the entry)
+/// and wakes any tasks waiting for it.
+#[cfg(feature = "std")]
+struct LoadGuard<'a, K: Eq + Hash + Clone, L: Lookup<K>> {
+    context: &'a CachedContext<K, L>,
+    key: &'a K,
+    value: Option<L::Value>,
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash + Clone, L: Lookup<K>> Drop for LoadGuard<'_, K, L> {
+    fn drop(&mut self) {
+        let mut cache = self.context.lock();
+        let previous = match self.value.take() {
+            Some(value) => cache.insert(self.key.clone(), Entry::Ready(Instant::now(), value)),
+            None => cache.remove(self.key),
+        };
+        drop(cache);
+        if let Some(Entry::Loading(wakers)) = previous {
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    /// Returns `Pending` the given number of times before completing.
+    struct YieldN(usize);
+
+    impl Future for YieldN {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                return Poll::Ready(());
+            }
+            self.0 -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    struct Item<'a> {
+        delay: usize,
+        valid: bool,
+        running: &'a Cell<usize>,
+        max_running: &'a Cell<usize>,
+    }
+
+    impl Validate for Item<'_> {
+        async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            self.running.set(self.running.get() + 1);
+            self.max_running
+                .set(self.max_running.get().max(self.running.get()));
+            YieldN(self.delay).await;
+            self.running.set(self.running.get() - 1);
+            if !self.valid {
+                accum.add_failure(format!("invalid after {}", self.delay));
+            }
+        }
+    }
+
+    fn check_concurrent(concurrency: Option<usize>, expected_max: usize) {
+        let running = Cell::new(0);
+        let max_running = Cell::new(0);
+        let items: Vec<_> = [(5, false), (1, true), (3, false), (0, false)]
+            .into_iter()
+            .map(|(delay, valid)| Item {
+                delay,
+                valid,
+                running: &running,
+                max_running: &max_running,
+            })
+            .collect();
+        let mut accum = Accumulator::new();
+        pollster::block_on(accum.validate_iter_concurrent(&items, concurrency));
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $[0]: invalid after 5
+   $[2]: invalid after 3
+   $[3]: invalid after 0"
+        );
+        assert_eq!(max_running.get(), expected_max);
+    }
+
+    #[test]
+    fn concurrent() {
+        check_concurrent(None, 4);
+        check_concurrent(Some(2), 2);
+        check_concurrent(Some(0), 1);
+    }
+
+    #[test]
+    fn join_limited_polls_woken() {
+        /// Counts its polls, and waits until `done` is set.
+        struct Waiting<'a> {
+            polls: Cell<usize>,
+            done: &'a Cell<bool>,
+            wakers: &'a RefCell<Vec<Waker>>,
+        }
+
+        impl Future for &Waiting<'_> {
+            type Output = ();
+
+            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+                self.polls.set(self.polls.get() + 1);
+                if self.done.get() {
+                    return Poll::Ready(());
+                }
+                self.wakers.borrow_mut().push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+
+        let done = Cell::new(false);
+        let wakers = RefCell::new(Vec::new());
+        let waiting: Vec<_> = (0..4)
+            .map(|_| Waiting {
+                polls: Cell::new(0),
+                done: &done,
+                wakers: &wakers,
+            })
+            .collect();
+        let busy = async {
+            YieldN(20).await;
+            done.set(true);
+            wakers.borrow_mut().drain(..).for_each(Waker::wake);
+        };
+        let others = join_limited(&waiting, None);
+        pollster::block_on(__join_front(busy, others));
+        for w in &waiting {
+            assert_eq!(w.polls.get(), 2);
+        }
+    }
+
+    struct Slow(usize);
+
+    impl Validate for Slow {
+        async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            accum.add_failure("partial");
+            YieldN(self.0).await;
+            accum.add_failure("done");
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let mut accum = Accumulator::new();
+        pollster::block_on(async {
+            accum
+                .validate_member_with_timeout_future("fast", &Slow(1), YieldN(3))
+                .await;
+            accum
+                .validate_member_with_timeout_future("slow", &Slow(5), YieldN(3))
+                .await;
+        });
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.fast: partial
+   $.fast: done
+   $.slow: validation timed out"
+        );
+    }
+
+    #[test]
+    fn cancellable() {
+        let result = pollster::block_on(validate_cancellable(&Slow(1), YieldN(3)));
+        assert_eq!(result.unwrap().unwrap_err().len(), 2);
+        let cancelled = pollster::block_on(validate_cancellable(&Slow(5), YieldN(3))).unwrap_err();
+        assert_eq!(cancelled.to_string(), "validation was cancelled");
+        assert_eq!(cancelled.into_partial().unwrap().len(), 1);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cached_context() {
+        struct Row(u32);
+
+        impl ValidateContext for Row {
+            type Context<'c> = CachedContext<u32, Lookups>;
+
+            async fn validate_inner_ctx<S: FailureSink>(
+                &self,
+                context: &Self::Context<'_>,
+                accum: &mut Accumulator<S>,
+            ) {
+                if !context.get(&self.0).await {
+                    accum.add_failure("missing");
+                }
+            }
+        }
+
+        struct Lookups(Cell<usize>);
+
+        impl Lookup<u32> for Lookups {
+            type Value = bool;
+
+            async fn lookup(&self, key: &u32) -> bool {
+                self.0.set(self.0.get() + 1);
+                YieldN(2).await;
+                *key > 0
+            }
+        }
+
+        let context = CachedContext::new(Lookups(Cell::new(0)));
+        let rows: Vec<_> = (0..6).map(|i| Row(i % 2)).collect();
+        let results = pollster::block_on(join_limited(
+            rows.iter().map(|r| r.validate_ctx(&context)),
+            None,
+        ));
+        assert_eq!(context.inner().0.get(), 2);
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 3);
+
+        context.invalidate(&0);
+        pollster::block_on(rows[0].validate_ctx(&context)).unwrap_err();
+        assert_eq!(context.inner().0.get(), 3);
+
+        let context = context.with_ttl(Duration::ZERO);
+        pollster::block_on(rows[1].validate_ctx(&context)).unwrap();
+        pollster::block_on(rows[1].validate_ctx(&context)).unwrap();
+        assert_eq!(context.inner().0.get(), 5);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cached_context_cancelled() {
+        let context = CachedContext::new(|key: &u32| {
+            let key = *key;
+            async move {
+                YieldN(2).await;
+                key
+            }
+        });
+        let cancelled = pollster::block_on(first(context.get(&1), YieldN(1)));
+        assert!(matches!(cancelled, Either::Right(())));
+        assert_eq!(pollster::block_on(context.get(&1)), 1);
+    }
+
+    #[test]
+    fn retry() {
+        let attempts = Cell::new(0);
+        let backoffs = Cell::new(0);
+        let policy = RetryPolicy::new(3).with_backoff(|n| {
+            backoffs.set(backoffs.get() + n);
+            std::future::ready(())
+        });
+        let check = || {
+            attempts.set(attempts.get() + 1);
+            let n = attempts.get();
+            async move {
+                match n {
+                    3 => Ok(n),
+                    _ => Err("flaky"),
+                }
+            }
+        };
+        let mut accum = Accumulator::new();
+        let value = pollster::block_on(accum.retry_at("a", &policy, check));
+        assert_eq!(value, Some(3));
+        assert_eq!(backoffs.get(), 1 + 2);
+        assert!(accum.is_empty());
+
+        let value = pollster::block_on(accum.retry_at("b", &RetryPolicy::new(0), check));
+        assert_eq!(value, None);
+        assert_eq!(attempts.get(), 4);
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $.b: could not be checked after 1 attempt(s): flaky"
+        );
+    }
+
+    #[test]
+    fn join_members() {
+        struct Pair {
+            a: Slow,
+            b: Slow,
+            c: Slow,
+        }
+
+        impl Validate for Pair {
+            async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+                crate::join_members!(accum; "a" => &self.a, "b" => &self.b, "c" => &self.c).await;
+            }
+        }
+
+        let pair = Pair {
+            a: Slow(3),
+            b: Slow(1),
+            c: Slow(0),
+        };
+        let err = pollster::block_on(pair.validate()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.a: partial
+   $.a: done
+   $.b: partial
+   $.b: done
+   $.c: partial
+   $.c: done"
+        );
+
+        let mut accum = Accumulator::new();
+        accum.set_max_depth(Some(0));
+        pollster::block_on(pair.validate_inner(&mut accum));
+        assert_eq!(accum.len(), 3);
+    }
 }
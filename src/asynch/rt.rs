@@ -0,0 +1,379 @@
+//! Integration with async runtimes.
+//!
+//! The async helpers in this crate do not depend on any runtime:
+//! anything needing a timer takes a future to await instead.
+//! The [Runtime] trait bridges the gap for helpers which need to sleep or run blocking work,
+//! and is implemented for [Tokio], [AsyncStd] and [Smol]
+//! behind the `tokio`, `async-std` and `smol` features respectively.
+//! Implement it for other runtimes as needed.
+//!
+//! Conversely, [BlockOn] lets async validators be used from synchronous code,
+//! with [valid_blocking] or the [Blocking] adapter.
+use std::{future::Future, sync::Arc, time::Duration};
+
+use super::Validate;
+use crate::{errors::Accumulator, FailureSink, Key, Valid};
+
+/// An async runtime which can sleep and run blocking work off the executor.
+pub trait Runtime {
+    /// A future which completes after `duration`.
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /// Run `f` on a thread where blocking is acceptable, returning its result.
+    ///
+    /// If `f` panics, the panic should be resumed when the returned future is polled.
+    fn spawn_blocking<F, R>(&self, f: F) -> impl Future<Output = R> + Send
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static;
+}
+
+impl<T: Runtime + ?Sized> Runtime for &T {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send {
+        (**self).sleep(duration)
+    }
+
+    fn spawn_blocking<F, R>(&self, f: F) -> impl Future<Output = R> + Send
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        (**self).spawn_blocking(f)
+    }
+}
+
+/// The [tokio](https://docs.rs/tokio) runtime.
+///
+/// Sleeping requires the runtime's time driver to be enabled.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tokio;
+
+#[cfg(feature = "tokio")]
+impl Runtime for Tokio {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+
+    fn spawn_blocking<F, R>(&self, f: F) -> impl Future<Output = R> + Send
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let handle = tokio::task::spawn_blocking(f);
+        async move {
+            match handle.await {
+                Ok(out) => out,
+                Err(e) => match e.try_into_panic() {
+                    Ok(payload) => std::panic::resume_unwind(payload),
+                    Err(e) => panic!("blocking task failed: {e}"),
+                },
+            }
+        }
+    }
+}
+
+/// The [async-std](https://docs.rs/async-std) runtime.
+#[cfg(feature = "async-std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStd;
+
+#[cfg(feature = "async-std")]
+impl Runtime for AsyncStd {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send {
+        async_std::task::sleep(duration)
+    }
+
+    fn spawn_blocking<F, R>(&self, f: F) -> impl Future<Output = R> + Send
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        async_std::task::spawn_blocking(f)
+    }
+}
+
+/// The [smol](https://docs.rs/smol) runtime.
+#[cfg(feature = "smol")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Smol;
+
+#[cfg(feature = "smol")]
+impl Runtime for Smol {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send {
+        let timer = smol::Timer::after(duration);
+        async move {
+            timer.await;
+        }
+    }
+
+    fn spawn_blocking<F, R>(&self, f: F) -> impl Future<Output = R> + Send
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        smol::unblock(f)
+    }
+}
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Validate an async member at the given key,
+    /// adding a failure there instead if it takes longer than `timeout`.
+    ///
+    /// See [Accumulator::validate_member_with_timeout_future] for runtimes without a [Runtime] implementation.
+    pub async fn validate_member_timeout<V: Validate + ?Sized>(
+        &mut self,
+        field: impl Into<Key>,
+        member: &V,
+        runtime: &impl Runtime,
+        timeout: Duration,
+    ) {
+        self.validate_member_with_timeout_future(field, member, runtime.sleep(timeout))
+            .await
+    }
+}
+
+/// Run synchronous validation on a thread where blocking is acceptable,
+/// so that expensive validators do not stall the executor.
+pub async fn validate_blocking<T>(runtime: &impl Runtime, value: Arc<T>) -> crate::Result
+where
+    T: crate::Validate + Send + Sync + ?Sized + 'static,
+{
+    runtime
+        .spawn_blocking(move || crate::Validate::validate(&*value))
+        .await
+}
+
+/// Something which can drive a future to completion from synchronous code.
+///
+/// Implemented for tokio's `Handle` and `Runtime` behind the `tokio` feature,
+/// and for [AsyncStd] and [Smol] behind their features.
+/// Implementations may panic if called from inside an async context.
+pub trait BlockOn {
+    /// Block the current thread until the future completes.
+    fn block_on<F: Future>(&self, future: F) -> F::Output;
+}
+
+impl<T: BlockOn + ?Sized> BlockOn for &T {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        (**self).block_on(future)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl BlockOn for tokio::runtime::Handle {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::runtime::Handle::block_on(self, future)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl BlockOn for tokio::runtime::Runtime {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        tokio::runtime::Runtime::block_on(self, future)
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl BlockOn for AsyncStd {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        async_std::task::block_on(future)
+    }
+}
+
+#[cfg(feature = "smol")]
+impl BlockOn for Smol {
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        smol::block_on(future)
+    }
+}
+
+/// Validate the value asynchronously, blocking until validation completes,
+/// and return the wrapped form.
+///
+/// ```
+/// # #[cfg(feature = "smol")] {
+/// use validatrix::{asynch::{rt::{valid_blocking, Smol}, Validate}, Accumulator, FailureSink};
+///
+/// struct Username(String);
+///
+/// impl Validate for Username {
+///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         // e.g. check the name is not already taken
+///         if self.0 == "admin" {
+///             accum.add_failure("is reserved");
+///         }
+///     }
+/// }
+///
+/// assert!(valid_blocking(Username("alice".into()), &Smol).is_ok());
+/// # }
+/// ```
+pub fn valid_blocking<T: Validate>(value: T, runtime: &impl BlockOn) -> crate::Result<Valid<T>> {
+    runtime.block_on(Valid::try_new_async(value))
+}
+
+/// Adapter which implements the synchronous [Validate](crate::Validate) for an async validator,
+/// by blocking on its validation.
+///
+/// This allows async validators to be used as members of synchronous ones.
+/// It must not be used inside async validation, which it would block.
+#[derive(Debug, Clone, Copy)]
+pub struct Blocking<'a, T: ?Sized, B> {
+    value: &'a T,
+    runtime: &'a B,
+}
+
+impl<'a, T: ?Sized, B> Blocking<'a, T, B> {
+    /// Wrap a reference to an async validator and something to block on it with.
+    pub fn new(value: &'a T, runtime: &'a B) -> Self {
+        Self { value, runtime }
+    }
+}
+
+impl<T: Validate + ?Sized, B: BlockOn> crate::Validate for Blocking<'_, T, B> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        self.runtime.block_on(self.value.validate_inner(accum))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs blocking work inline and wakes sleepers immediately.
+    struct Inline;
+
+    impl BlockOn for Inline {
+        fn block_on<F: Future>(&self, future: F) -> F::Output {
+            pollster::block_on(future)
+        }
+    }
+
+    impl Runtime for Inline {
+        fn sleep(&self, _duration: Duration) -> impl Future<Output = ()> + Send {
+            std::future::ready(())
+        }
+
+        fn spawn_blocking<F, R>(&self, f: F) -> impl Future<Output = R> + Send
+        where
+            F: FnOnce() -> R + Send + 'static,
+            R: Send + 'static,
+        {
+            std::future::ready(f())
+        }
+    }
+
+    struct Even(u8);
+
+    impl crate::Validate for Even {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if self.0 % 2 != 0 {
+                accum.add_failure("value is odd");
+            }
+        }
+    }
+
+    struct Slow;
+
+    impl Validate for Slow {
+        async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            accum.add_failure("started");
+            std::future::pending::<()>().await
+        }
+    }
+
+    async fn check(runtime: impl Runtime) {
+        assert!(validate_blocking(&runtime, Arc::new(Even(2))).await.is_ok());
+        assert!(validate_blocking(&runtime, Arc::new(Even(3)))
+            .await
+            .is_err());
+
+        let mut accum = Accumulator::new();
+        accum
+            .validate_member_timeout("slow", &Slow, &runtime, Duration::from_millis(1))
+            .await;
+        assert_eq!(
+            crate::Result::from(accum).unwrap_err().to_string(),
+            "Validation failure(s):\n   $.slow: validation timed out",
+        );
+    }
+
+    #[test]
+    fn inline() {
+        pollster::block_on(check(Inline));
+    }
+
+    struct Name(&'static str);
+
+    impl Validate for Name {
+        async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if self.0.is_empty() {
+                accum.add_failure("must not be empty");
+            }
+        }
+    }
+
+    /// A synchronous validator using the runtime under test to validate its async members.
+    struct Names<'a, B> {
+        names: Vec<Name>,
+        runtime: &'a B,
+    }
+
+    impl<B: BlockOn> crate::Validate for Names<'_, B> {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            for (idx, name) in self.names.iter().enumerate() {
+                accum.validate_member_at(idx, &Blocking::new(name, self.runtime));
+            }
+        }
+    }
+
+    fn check_blocking<B: BlockOn>(runtime: &B) {
+        assert_eq!(valid_blocking(Name("a"), runtime).unwrap().0, "a");
+        assert!(valid_blocking(Name(""), runtime).is_err());
+
+        let names = Names {
+            names: vec![Name("a"), Name("")],
+            runtime,
+        };
+        let err = crate::Validate::validate(&names).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1]: must not be empty"
+        );
+    }
+
+    #[test]
+    fn blocking_inline() {
+        check_blocking(&Inline);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio() {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(check(Tokio));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        check_blocking(&runtime);
+        check_blocking(runtime.handle());
+    }
+
+    #[cfg(feature = "async-std")]
+    #[test]
+    fn async_std() {
+        async_std::task::block_on(check(AsyncStd));
+        check_blocking(&AsyncStd);
+    }
+
+    #[cfg(feature = "smol")]
+    #[test]
+    fn smol() {
+        smol::block_on(check(Smol));
+        check_blocking(&Smol);
+    }
+}
@@ -0,0 +1,192 @@
+//! Validating the items of a [Stream] as they arrive.
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use super::Validate;
+use crate::{Key, Valid};
+
+/// Validate each item of the stream as it arrives,
+/// with the item's index in the stream at the start of failure paths.
+///
+/// Items are validated one at a time, in order.
+/// See also [StreamValidateExt::validated] and [validate_stream_all].
+///
+/// ```
+/// use futures::{stream, StreamExt};
+/// use validatrix::{asynch::validate_stream, Accumulator, FailureSink, Validate};
+///
+/// #[derive(Debug)]
+/// struct Even(u8);
+///
+/// impl Validate for Even {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 % 2 != 0 {
+///             accum.add_failure("odd");
+///         }
+///     }
+/// }
+///
+/// let items = stream::iter([Even(2), Even(3)]);
+/// let results: Vec<_> = futures::executor::block_on(validate_stream(items).collect());
+/// assert_eq!(results[0].as_ref().unwrap().0, 2);
+/// assert_eq!(
+///     results[1].as_ref().unwrap_err().to_string(),
+///     "Validation failure(s):\n   $[1]: odd",
+/// );
+/// ```
+pub fn validate_stream<S>(stream: S) -> impl Stream<Item = crate::Result<Valid<S::Item>>>
+where
+    S: Stream,
+    S::Item: Validate,
+{
+    ValidateStream {
+        stream: Box::pin(stream),
+        validate: |item: S::Item| async move {
+            let result = item.validate().await;
+            (item, result)
+        },
+        pending: None,
+        index: 0,
+    }
+}
+
+/// Validate every item of the stream, returning them all if they are valid,
+/// or a single [Error](crate::Error) with all of the failures.
+///
+/// ```
+/// use futures::stream;
+/// use validatrix::{asynch::validate_stream_all, Accumulator, FailureSink, Validate};
+///
+/// #[derive(Debug)]
+/// struct Even(u8);
+///
+/// impl Validate for Even {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 % 2 != 0 {
+///             accum.add_failure("odd");
+///         }
+///     }
+/// }
+///
+/// let items = stream::iter([Even(1), Even(2), Even(3)]);
+/// let err = futures::executor::block_on(validate_stream_all(items)).unwrap_err();
+/// assert_eq!(err.to_string(), "Validation failure(s):\n   $[0]: odd\n   $[2]: odd");
+/// ```
+pub async fn validate_stream_all<S>(stream: S) -> crate::Result<Vec<Valid<S::Item>>>
+where
+    S: Stream,
+    S::Item: Validate,
+{
+    let mut stream = core::pin::pin!(validate_stream(stream));
+    let mut valid = Vec::new();
+    let mut error: Option<crate::Error> = None;
+    while let Some(result) = core::future::poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        match (result, error.as_mut()) {
+            (Ok(item), None) => valid.push(item),
+            (Ok(_), Some(_)) => (),
+            (Err(e), None) => error = Some(e),
+            (Err(e), Some(error)) => error.merge(e),
+        }
+    }
+    match error {
+        Some(error) => Err(error),
+        None => Ok(valid),
+    }
+}
+
+/// Extension trait for validating the items of a [Stream].
+pub trait StreamValidateExt: Stream + Sized {
+    /// Validate each item as it arrives; see [validate_stream].
+    fn validated(self) -> impl Stream<Item = crate::Result<Valid<Self::Item>>>
+    where
+        Self::Item: Validate,
+    {
+        validate_stream(self)
+    }
+}
+
+impl<S: Stream> StreamValidateExt for S {}
+
+struct ValidateStream<S, F, Fut> {
+    stream: Pin<Box<S>>,
+    validate: F,
+    pending: Option<Pin<Box<Fut>>>,
+    index: usize,
+}
+
+impl<S, F, Fut> Stream for ValidateStream<S, F, Fut>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> Fut + Unpin,
+    Fut: Future<Output = (S::Item, crate::Result)>,
+{
+    type Item = crate::Result<Valid<S::Item>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(pending) = this.pending.as_mut() {
+                let (item, result) = core::task::ready!(pending.as_mut().poll(cx));
+                this.pending = None;
+                let index = this.index;
+                this.index += 1;
+                return Poll::Ready(Some(match result {
+                    Ok(()) => Ok(Valid::new_unchecked(item)),
+                    Err(mut e) => {
+                        e.rebase(&[Key::Index(index)]);
+                        Err(e)
+                    }
+                }));
+            }
+            match core::task::ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(item) => this.pending = Some(Box::pin((this.validate)(item))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{stream, StreamExt};
+
+    use super::*;
+    use crate::{Accumulator, FailureSink};
+
+    struct Item(bool);
+
+    impl crate::Validate for Item {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            accum.with_key("ok", |a| {
+                if !self.0 {
+                    a.add_failure("bad")
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn ext() {
+        let items = stream::iter([Item(false), Item(true), Item(false)]);
+        let paths: Vec<_> = futures::executor::block_on(
+            items
+                .validated()
+                .map(|r| r.err().map(|e| e.failures()[0].json_path()))
+                .collect(),
+        );
+        assert_eq!(
+            paths,
+            [
+                Some("$[0].ok".to_string()),
+                None,
+                Some("$[2].ok".to_string())
+            ]
+        );
+    }
+}
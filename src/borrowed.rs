@@ -0,0 +1,471 @@
+//! Validation reports which borrow from the validated value.
+//!
+//! An [Error](crate::Error) owns every failure's path and message,
+//! so that it can be stored, sent between threads and reported later.
+//! Hot paths which only render the report immediately can avoid most of that allocation
+//! by implementing this module's [Validate] instead:
+//! messages and keys may borrow from the value (or be `'static`),
+//! and the resulting [ErrorRef] stores them all in two buffers.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use validatrix::borrowed::{AccumulatorRef, KeyRef, Validate};
+//!
+//! struct Row {
+//!     status: String,
+//!     labels: BTreeMap<String, String>,
+//! }
+//!
+//! impl Validate for Row {
+//!     fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+//!         if !["active", "retired"].contains(&self.status.as_str()) {
+//!             accum.add_failure_at("status", "unknown status");
+//!         }
+//!         accum.with_key("labels", |a| {
+//!             for (name, value) in &self.labels {
+//!                 if value.is_empty() {
+//!                     // key borrowed from the value
+//!                     a.add_failure_at(KeyRef::MapKey(name), "label is empty");
+//!                 }
+//!             }
+//!         });
+//!     }
+//! }
+//!
+//! let row = Row {
+//!     status: "pending".into(),
+//!     labels: [("team".to_string(), String::new())].into_iter().collect(),
+//! };
+//! assert_eq!(
+//!     row.validate().unwrap_err().to_string(),
+//!     "Validation failure(s):\n   $.status: unknown status\n   $.labels[\"team\"]: label is empty",
+//! );
+//! ```
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{Display, Write},
+    ops::Range,
+};
+
+use crate::{
+    errors::{write_bracketed, INDENT},
+    Failure, Key, Severity,
+};
+
+/// Borrowed counterpart of [crate::Validate],
+/// whose failures can borrow from the value being validated.
+pub trait Validate {
+    /// Add any failures to the accumulator.
+    fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>);
+
+    /// Validate the value, returning a report which borrows from it if it is invalid.
+    fn validate(&self) -> Result<(), ErrorRef<'_>> {
+        let mut accum = AccumulatorRef::new();
+        self.validate_inner(&mut accum);
+        accum.into_result()
+    }
+}
+
+impl<T: Validate> Validate for [T] {
+    fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+        accum.validate_iter(self)
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+        accum.validate_iter(self)
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for &T {
+    fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+        (**self).validate_inner(accum)
+    }
+}
+
+/// Borrowed counterpart of [Key].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyRef<'a> {
+    /// Index into a sequence, displayed like `[0]`.
+    Index(usize),
+    /// Name of a struct field, displayed like `.field`.
+    Field(&'a str),
+    /// Key of a map entry, displayed like `["key"]`.
+    MapKey(&'a str),
+}
+
+impl KeyRef<'_> {
+    /// Copy into an owned [Key].
+    pub fn to_key(&self) -> Key {
+        match *self {
+            KeyRef::Index(n) => Key::Index(n),
+            KeyRef::Field(s) => Key::Field(String::from(s).into()),
+            KeyRef::MapKey(s) => Key::MapKey(String::from(s).into()),
+        }
+    }
+}
+
+impl From<usize> for KeyRef<'_> {
+    fn from(value: usize) -> Self {
+        Self::Index(value)
+    }
+}
+
+impl<'a> From<&'a str> for KeyRef<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::Field(value)
+    }
+}
+
+/// Formatted like [Key].
+impl Display for KeyRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            KeyRef::Index(n) => write!(f, "[{n}]"),
+            KeyRef::Field(s) => {
+                f.write_char('.')?;
+                f.write_str(s)
+            }
+            KeyRef::MapKey(s) => write_bracketed(f, s),
+        }
+    }
+}
+
+/// Borrowed counterpart of [Accumulator](crate::Accumulator),
+/// which collects failures borrowing from the value being validated.
+///
+/// Paths and messages are stored in shared buffers, which are only allocated once there is a failure.
+#[derive(Debug, Default)]
+pub struct AccumulatorRef<'a> {
+    prefix: Vec<KeyRef<'a>>,
+    report: ErrorRef<'a>,
+}
+
+impl<'a> AccumulatorRef<'a> {
+    /// Create an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a failure at the current prefix.
+    pub fn add_failure(&mut self, message: &'a str) {
+        self.report
+            .push(&self.prefix, None, message, Severity::Error);
+    }
+
+    /// Add a failure at the given key, under the current prefix.
+    pub fn add_failure_at(&mut self, key: impl Into<KeyRef<'a>>, message: &'a str) {
+        self.report
+            .push(&self.prefix, Some(key.into()), message, Severity::Error);
+    }
+
+    /// Add a warning at the current prefix.
+    pub fn add_warning(&mut self, message: &'a str) {
+        self.report
+            .push(&self.prefix, None, message, Severity::Warning);
+    }
+
+    /// Add a warning at the given key, under the current prefix.
+    pub fn add_warning_at(&mut self, key: impl Into<KeyRef<'a>>, message: &'a str) {
+        self.report
+            .push(&self.prefix, Some(key.into()), message, Severity::Warning);
+    }
+
+    /// Run the given function with the key added to the prefix.
+    pub fn with_key(&mut self, key: impl Into<KeyRef<'a>>, f: impl FnOnce(&mut Self)) {
+        self.prefix.push(key.into());
+        f(self);
+        self.prefix.pop();
+    }
+
+    /// Validate a member at the given key.
+    pub fn validate_member_at<V: Validate + ?Sized>(
+        &mut self,
+        key: impl Into<KeyRef<'a>>,
+        member: &'a V,
+    ) {
+        self.with_key(key, |a| member.validate_inner(a))
+    }
+
+    /// Validate each item of a collection at its index.
+    pub fn validate_iter<V: Validate + 'a>(&mut self, items: impl IntoIterator<Item = &'a V>) {
+        for (idx, item) in items.into_iter().enumerate() {
+            self.validate_member_at(idx, item);
+        }
+    }
+
+    /// Convenience method to do [Self::validate_iter] for a given key.
+    pub fn validate_iter_at<V: Validate + 'a>(
+        &mut self,
+        key: impl Into<KeyRef<'a>>,
+        items: impl IntoIterator<Item = &'a V>,
+    ) {
+        self.with_key(key, |a| a.validate_iter(items))
+    }
+
+    /// Validate each value of a map at its key, which is borrowed from the map.
+    pub fn validate_map<K, V>(&mut self, items: impl IntoIterator<Item = (&'a K, &'a V)>)
+    where
+        K: AsRef<str> + ?Sized + 'a,
+        V: Validate + 'a,
+    {
+        for (key, value) in items {
+            self.validate_member_at(KeyRef::MapKey(key.as_ref()), value);
+        }
+    }
+
+    /// Number of failures added so far.
+    pub fn len(&self) -> usize {
+        self.report.len()
+    }
+
+    /// Whether no failures have been added.
+    pub fn is_empty(&self) -> bool {
+        self.report.is_empty()
+    }
+
+    /// Whether any failures have [Severity::Error].
+    pub fn has_errors(&self) -> bool {
+        self.report.has_errors()
+    }
+
+    /// Discard all failures, keeping the allocated buffers.
+    pub fn clear(&mut self) {
+        self.prefix.clear();
+        self.report.clear();
+    }
+
+    /// Finish validation.
+    ///
+    /// Like an [Accumulator](crate::Accumulator), this is only an error if any failures have [Severity::Error].
+    pub fn into_result(self) -> Result<(), ErrorRef<'a>> {
+        if self.report.has_errors() {
+            Err(self.report)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Borrowed counterpart of [Error](crate::Error), whose keys and messages borrow from the validated value.
+///
+/// Formatted like [Error](crate::Error); use [ErrorRef::to_error] to keep it for later.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ErrorRef<'a> {
+    keys: Vec<KeyRef<'a>>,
+    records: Vec<Record<'a>>,
+    n_errors: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Record<'a> {
+    keys: Range<usize>,
+    message: &'a str,
+    severity: Severity,
+}
+
+impl<'a> ErrorRef<'a> {
+    fn push(
+        &mut self,
+        prefix: &[KeyRef<'a>],
+        key: Option<KeyRef<'a>>,
+        message: &'a str,
+        severity: Severity,
+    ) {
+        let start = self.keys.len();
+        self.keys.extend_from_slice(prefix);
+        self.keys.extend(key);
+        if severity == Severity::Error {
+            self.n_errors += 1;
+        }
+        self.records.push(Record {
+            keys: start..self.keys.len(),
+            message,
+            severity,
+        });
+    }
+
+    fn clear(&mut self) {
+        self.keys.clear();
+        self.records.clear();
+        self.n_errors = 0;
+    }
+
+    /// Number of failures.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Whether any failures have [Severity::Error].
+    pub fn has_errors(&self) -> bool {
+        self.n_errors > 0
+    }
+
+    /// Iterate over the failures, in the order they were added.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = FailureRef<'_, 'a>> + '_ {
+        self.records.iter().map(|r| FailureRef {
+            keys: &self.keys[r.keys.clone()],
+            message: r.message,
+            severity: r.severity,
+        })
+    }
+
+    /// Copy the failures into an owned [Error](crate::Error).
+    pub fn to_error(&self) -> crate::Error {
+        self.iter().map(|f| f.to_failure()).collect()
+    }
+}
+
+/// Formatted like [Error](crate::Error).
+impl Display for ErrorRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Validation failure(s):")?;
+        for failure in self.iter() {
+            f.write_char('\n')?;
+            f.write_str(INDENT)?;
+            failure.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A failure in an [ErrorRef].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailureRef<'r, 'a> {
+    keys: &'r [KeyRef<'a>],
+    message: &'a str,
+    severity: Severity,
+}
+
+impl<'r, 'a> FailureRef<'r, 'a> {
+    /// Path to this failure.
+    pub fn keys(&self) -> &'r [KeyRef<'a>] {
+        self.keys
+    }
+
+    /// Description of this failure.
+    pub fn message(&self) -> &'a str {
+        self.message
+    }
+
+    /// How serious this failure is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Copy into an owned [Failure].
+    pub fn to_failure(&self) -> Failure {
+        let keys: Vec<_> = self.keys.iter().map(KeyRef::to_key).collect();
+        Failure::new(&keys, String::from(self.message)).with_severity(self.severity)
+    }
+}
+
+/// Formatted like [Failure].
+impl Display for FailureRef<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_char('$')?;
+        for key in self.keys {
+            key.fmt(f)?;
+        }
+        if self.severity == Severity::Warning {
+            f.write_str(" (warning)")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    struct Config {
+        name: String,
+        env: BTreeMap<String, Var>,
+        ports: Vec<Port>,
+    }
+
+    struct Var(String);
+
+    struct Port(u16);
+
+    impl Validate for Config {
+        fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+            if self.name.is_empty() {
+                accum.add_failure_at("name", "must not be empty");
+            }
+            accum.with_key("env", |a| a.validate_map(&self.env));
+            accum.validate_iter_at("ports", &self.ports);
+        }
+    }
+
+    impl Validate for Var {
+        fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+            if self.0.contains('\n') {
+                // message borrowed from the value
+                accum.add_failure(&self.0);
+            }
+        }
+    }
+
+    impl Validate for Port {
+        fn validate_inner<'a>(&'a self, accum: &mut AccumulatorRef<'a>) {
+            if self.0 < 1024 {
+                accum.add_warning("is privileged");
+            }
+        }
+    }
+
+    fn config() -> Config {
+        Config {
+            name: String::new(),
+            env: [("PATH".into(), Var("a\nb".into()))].into_iter().collect(),
+            ports: vec![Port(8080), Port(80)],
+        }
+    }
+
+    #[test]
+    fn matches_owned() {
+        let config = config();
+        let err = config.validate().unwrap_err();
+        let expected = "Validation failure(s):\n   $.name: must not be empty\n   $.env[\"PATH\"]: a\nb\n   $.ports[1] (warning): is privileged";
+        assert_eq!(err.to_string(), expected);
+        assert_eq!(err.to_error().to_string(), expected);
+        assert_eq!(err.len(), 3);
+        let failures: Vec<_> = err.iter().collect();
+        assert_eq!(
+            failures[1].keys(),
+            [KeyRef::Field("env"), KeyRef::MapKey("PATH")]
+        );
+        assert_eq!(failures[2].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn warnings_are_ok() {
+        let mut accum = AccumulatorRef::new();
+        accum.validate_member_at("port", &Port(22));
+        assert_eq!(accum.len(), 1);
+        assert!(!accum.has_errors());
+        assert!(accum.into_result().is_ok());
+    }
+
+    #[test]
+    fn clear() {
+        let config = config();
+        let mut accum = AccumulatorRef::new();
+        config.validate_inner(&mut accum);
+        assert!(accum.has_errors());
+        accum.clear();
+        assert!(accum.is_empty());
+        accum.validate_iter(&config.ports);
+        assert_eq!(accum.len(), 1);
+    }
+}
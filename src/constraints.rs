@@ -0,0 +1,49 @@
+//! Ready-made constraints for common kinds of value.
+//!
+//! Each constraint is a function which takes an [Accumulator](crate::Accumulator) and the value to check,
+//! adding any failures at the accumulator's current prefix.
+//! Use [Accumulator::with_key](crate::Accumulator::with_key) to apply one to a member.
+//!
+//! ```
+//! use validatrix::{constraints, Accumulator, FailureSink, Validate};
+//!
+//! struct Payment {
+//!     card_number: String,
+//! }
+//!
+//! impl Validate for Payment {
+//!     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+//!         accum.with_key("card_number", |a| constraints::luhn(a, &self.card_number));
+//!     }
+//! }
+//!
+//! assert!(Payment { card_number: "4111 1111 1111 1111".into() }.validate().is_ok());
+//! assert!(Payment { card_number: "4111 1111 1111 1112".into() }.validate().is_err());
+//! ```
+mod checksum;
+pub use checksum::{checksum_digit, luhn, luhn_check_digit};
+#[cfg(feature = "cron")]
+mod cron_expr;
+#[cfg(feature = "cron")]
+pub use cron_expr::cron;
+mod geo;
+pub use geo::{lat_lon, latitude, longitude, percentage, unit_interval};
+#[cfg(feature = "glob")]
+mod glob_pattern;
+#[cfg(feature = "glob")]
+pub use glob_pattern::glob;
+mod password;
+pub use password::{password, BannedHook, PasswordPolicy};
+
+/// Apply a constraint with a fresh accumulator, returning the messages of any failures.
+#[cfg(test)]
+fn messages(
+    constraint: impl FnOnce(&mut crate::Accumulator),
+) -> alloc::vec::Vec<alloc::string::String> {
+    let mut accum = crate::Accumulator::new();
+    constraint(&mut accum);
+    match accum.take_result() {
+        Ok(()) => alloc::vec::Vec::new(),
+        Err(e) => e.iter().map(|f| f.message().into()).collect(),
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Accumulator, FailureSink};
+use alloc::{format, vec::Vec};
+
+/// Whether a character is ignored when reading digits for a checksum.
+fn is_separator(c: char) -> bool {
+    c == ' ' || c == '-'
+}
+
+/// Check that the last digit of `value` is the check digit computed from the others.
+///
+/// Spaces and hyphens are ignored.
+/// `check_digit` is given the value of every digit before the last, and returns the expected last digit.
+///
+/// Failure messages never include the value itself,
+/// as these are often sensitive (e.g. card numbers).
+pub fn checksum_digit<S: FailureSink>(
+    accum: &mut Accumulator<S>,
+    value: &str,
+    check_digit: impl FnOnce(&[u8]) -> u8,
+) {
+    let mut digits = Vec::with_capacity(value.len());
+    for (idx, c) in value.chars().enumerate() {
+        if is_separator(c) {
+            continue;
+        }
+        match c.to_digit(10) {
+            Some(d) => digits.push(d as u8),
+            None => {
+                accum.add_failure(format!("non-digit character at position {idx}"));
+                return;
+            }
+        }
+    }
+
+    let Some((last, payload)) = digits.split_last() else {
+        accum.add_failure("no digits");
+        return;
+    };
+    if payload.is_empty() {
+        accum.add_failure("too few digits for a check digit");
+        return;
+    }
+    if check_digit(payload) != *last {
+        accum.add_failure("check digit does not match");
+    }
+}
+
+/// Compute the [Luhn](https://en.wikipedia.org/wiki/Luhn_algorithm) check digit
+/// for the given digits (which do not include a check digit).
+pub fn luhn_check_digit(payload: &[u8]) -> u8 {
+    let sum: u32 = payload
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(idx, d)| {
+            let d = u32::from(*d);
+            if idx % 2 == 0 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Check that a string of digits (e.g. a card number or IMEI) has a valid Luhn check digit.
+///
+/// See [checksum_digit].
+pub fn luhn<S: FailureSink>(accum: &mut Accumulator<S>, value: &str) {
+    checksum_digit(accum, value, luhn_check_digit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::messages;
+
+    #[test]
+    fn luhn_valid() {
+        for v in [
+            "79927398713",
+            "4111 1111 1111 1111",
+            "4111-1111-1111-1111",
+            "490154203237518",
+        ] {
+            assert!(messages(|a| luhn(a, v)).is_empty(), "{v}");
+        }
+    }
+
+    #[test]
+    fn luhn_invalid() {
+        assert_eq!(
+            messages(|a| luhn(a, "79927398710")),
+            vec!["check digit does not match"]
+        );
+    }
+
+    #[test]
+    fn luhn_does_not_echo() {
+        let msgs = messages(|a| luhn(a, "4111x111"));
+        assert_eq!(msgs, vec!["non-digit character at position 4"]);
+    }
+
+    #[test]
+    fn too_short() {
+        assert_eq!(messages(|a| luhn(a, "")), vec!["no digits"]);
+        assert_eq!(
+            messages(|a| luhn(a, "1")),
+            vec!["too few digits for a check digit"]
+        );
+    }
+
+    #[test]
+    fn custom_checksum() {
+        // mod-10 sum of digits
+        let check = |p: &[u8]| (p.iter().map(|d| u32::from(*d)).sum::<u32>() % 10) as u8;
+        assert!(messages(|a| checksum_digit(a, "1236", check)).is_empty());
+        assert_eq!(messages(|a| checksum_digit(a, "1237", check)).len(), 1);
+    }
+}
@@ -0,0 +1,88 @@
+use core::str::FromStr;
+
+use crate::{Accumulator, FailureSink};
+
+/// Find the whitespace-separated token containing the given byte offset.
+fn token_at(expr: &str, offset: usize) -> Option<&str> {
+    let mut start = 0;
+    for token in expr.split(' ') {
+        let end = start + token.len();
+        if !token.is_empty() && offset >= start && offset < end {
+            return Some(token);
+        }
+        start = end + 1;
+    }
+    None
+}
+
+/// Check that a string is a valid cron expression.
+///
+/// Standard 5-field expressions (minute to day of week) are accepted,
+/// as are 6- and 7-field expressions which add seconds and years.
+/// Failure messages name the offending token where possible.
+///
+/// Requires the `cron` feature.
+pub fn cron<S: FailureSink>(accum: &mut Accumulator<S>, value: &str) {
+    let normalized = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    let n_fields = normalized.split(' ').filter(|t| !t.is_empty()).count();
+    let expr = match n_fields {
+        5 => format!("0 {normalized}"),
+        6 | 7 => normalized,
+        n => {
+            accum.add_failure(format!(
+                "cron expression must have 5 to 7 fields, found {n}"
+            ));
+            return;
+        }
+    };
+
+    let Err(e) = cron::Schedule::from_str(&expr) else {
+        return;
+    };
+    // errors are rendered as the expression, a caret under the problem, and a reason
+    let rendered = e.to_string();
+    let mut lines = rendered.lines();
+    let caret = lines.nth(1).and_then(|l| l.find('^'));
+    let reason = lines.last().map(str::trim).filter(|r| !r.is_empty());
+    let token = caret.and_then(|c| token_at(&expr, c));
+
+    match (token, reason) {
+        (Some(t), Some(r)) => accum.add_failure(format!("invalid cron expression at '{t}': {r}")),
+        (Some(t), None) => accum.add_failure(format!("invalid cron expression at '{t}'")),
+        (None, Some(r)) => accum.add_failure(format!("invalid cron expression: {r}")),
+        (None, None) => accum.add_failure("invalid cron expression"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::messages;
+
+    #[test]
+    fn valid() {
+        for v in [
+            "* * * * *",
+            "*/5 9-17 * * MON-FRI",
+            "0 0 12 * * * 2030",
+            "0  0 * * *",
+        ] {
+            assert_eq!(messages(|a| cron(a, v)), Vec::<String>::new(), "{v}");
+        }
+    }
+
+    #[test]
+    fn wrong_field_count() {
+        assert_eq!(
+            messages(|a| cron(a, "* * *")),
+            vec!["cron expression must have 5 to 7 fields, found 3"]
+        );
+    }
+
+    #[test]
+    fn reports_token() {
+        let msgs = messages(|a| cron(a, "0 61 * * *"));
+        assert_eq!(msgs.len(), 1);
+        assert!(msgs[0].contains("'61'"), "{}", msgs[0]);
+    }
+}
@@ -0,0 +1,79 @@
+use alloc::format;
+use core::ops::RangeInclusive;
+
+use crate::{Accumulator, FailureSink};
+
+fn in_range<S: FailureSink>(
+    accum: &mut Accumulator<S>,
+    value: f64,
+    range: RangeInclusive<f64>,
+    what: &str,
+) {
+    if value.is_nan() {
+        accum.add_failure(format!("{what} must be a number"));
+    } else if !range.contains(&value) {
+        accum.add_failure(format!(
+            "{what} {value} is outside the range {} to {}",
+            range.start(),
+            range.end()
+        ));
+    }
+}
+
+/// Check that a latitude in degrees is between -90 and 90 inclusive.
+pub fn latitude<S: FailureSink>(accum: &mut Accumulator<S>, value: f64) {
+    in_range(accum, value, -90.0..=90.0, "latitude")
+}
+
+/// Check that a longitude in degrees is between -180 and 180 inclusive.
+pub fn longitude<S: FailureSink>(accum: &mut Accumulator<S>, value: f64) {
+    in_range(accum, value, -180.0..=180.0, "longitude")
+}
+
+/// Check a latitude and longitude pair,
+/// adding failures at the keys `lat` and `lon` respectively.
+pub fn lat_lon<S: FailureSink>(accum: &mut Accumulator<S>, lat: f64, lon: f64) {
+    accum.with_key("lat", |a| latitude(a, lat));
+    accum.with_key("lon", |a| longitude(a, lon));
+}
+
+/// Check that a percentage is between 0 and 100 inclusive.
+pub fn percentage<S: FailureSink>(accum: &mut Accumulator<S>, value: f64) {
+    in_range(accum, value, 0.0..=100.0, "percentage")
+}
+
+/// Check that a value is between 0 and 1 inclusive.
+pub fn unit_interval<S: FailureSink>(accum: &mut Accumulator<S>, value: f64) {
+    in_range(accum, value, 0.0..=1.0, "value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::messages;
+
+    #[test]
+    fn ranges() {
+        assert!(messages(|a| latitude(a, 90.0)).is_empty());
+        assert_eq!(messages(|a| latitude(a, -90.1)).len(), 1);
+        assert!(messages(|a| longitude(a, -180.0)).is_empty());
+        assert_eq!(messages(|a| longitude(a, 180.5)).len(), 1);
+        assert!(messages(|a| percentage(a, 100.0)).is_empty());
+        assert_eq!(messages(|a| percentage(a, -1.0)).len(), 1);
+        assert!(messages(|a| unit_interval(a, 0.5)).is_empty());
+        assert_eq!(messages(|a| unit_interval(a, f64::NAN)).len(), 1);
+    }
+
+    #[test]
+    fn paired() {
+        let mut accum = Accumulator::new();
+        lat_lon(&mut accum, 91.0, 181.0);
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.lat: latitude 91 is outside the range -90 to 90
+   $.lon: longitude 181 is outside the range -180 to 180"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{Accumulator, FailureSink};
+
+/// Check that a string is a valid glob pattern, as understood by the [glob](https://docs.rs/glob) crate.
+///
+/// Failure messages name the path component containing the problem.
+///
+/// Requires the `glob` feature.
+pub fn glob<S: FailureSink>(accum: &mut Accumulator<S>, value: &str) {
+    let Err(e) = glob::Pattern::new(value) else {
+        return;
+    };
+    // pos is a character index
+    let byte_pos = value
+        .char_indices()
+        .nth(e.pos)
+        .map_or(value.len(), |(idx, _)| idx);
+    let start = value[..byte_pos].rfind('/').map_or(0, |idx| idx + 1);
+    let end = value[byte_pos..]
+        .find('/')
+        .map_or(value.len(), |idx| byte_pos + idx);
+    accum.add_failure(format!(
+        "invalid glob pattern at '{}': {}",
+        &value[start..end],
+        e.msg
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::messages;
+
+    #[test]
+    fn valid() {
+        for v in ["*.rs", "src/**/*.rs", "[abc]?.txt"] {
+            assert_eq!(messages(|a| glob(a, v)), Vec::<String>::new(), "{v}");
+        }
+    }
+
+    #[test]
+    fn reports_component() {
+        assert_eq!(
+            messages(|a| glob(a, "src/***/lib.rs")),
+            vec![
+                "invalid glob pattern at '***': wildcards are either regular `*` or recursive `**`"
+            ]
+        );
+        assert_eq!(
+            messages(|a| glob(a, "a/[b")),
+            vec!["invalid glob pattern at '[b': invalid range pattern"]
+        );
+    }
+}
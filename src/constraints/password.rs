@@ -0,0 +1,149 @@
+use crate::{Accumulator, FailureSink};
+use alloc::{boxed::Box, format};
+
+/// Hook deciding whether a password is banned; see [PasswordPolicy::is_banned].
+pub type BannedHook = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Requirements for a password, for use with [password].
+///
+/// The default policy only requires a minimum length of 8 characters.
+pub struct PasswordPolicy {
+    /// Minimum number of characters.
+    pub min_length: usize,
+    /// Maximum number of characters, if any.
+    pub max_length: Option<usize>,
+    /// Must contain a lowercase letter.
+    pub require_lowercase: bool,
+    /// Must contain an uppercase letter.
+    pub require_uppercase: bool,
+    /// Must contain a decimal digit.
+    pub require_digit: bool,
+    /// Must contain a character which is not alphanumeric or whitespace.
+    pub require_symbol: bool,
+    /// Returns `true` if the password is banned (e.g. appears in a list of common passwords).
+    pub is_banned: Option<BannedHook>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: None,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            is_banned: None,
+        }
+    }
+}
+
+impl core::fmt::Debug for PasswordPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PasswordPolicy")
+            .field("min_length", &self.min_length)
+            .field("max_length", &self.max_length)
+            .field("require_lowercase", &self.require_lowercase)
+            .field("require_uppercase", &self.require_uppercase)
+            .field("require_digit", &self.require_digit)
+            .field("require_symbol", &self.require_symbol)
+            .field("is_banned", &self.is_banned.is_some())
+            .finish()
+    }
+}
+
+/// Check a password against a [PasswordPolicy].
+///
+/// Every unmet criterion produces its own failure at the current prefix,
+/// so that the full list can be shown to a user.
+/// Lengths are counted in characters, not bytes.
+pub fn password<S: FailureSink>(accum: &mut Accumulator<S>, value: &str, policy: &PasswordPolicy) {
+    let mut len = 0;
+    let mut lowercase = false;
+    let mut uppercase = false;
+    let mut digit = false;
+    let mut symbol = false;
+    for c in value.chars() {
+        len += 1;
+        lowercase |= c.is_lowercase();
+        uppercase |= c.is_uppercase();
+        digit |= c.is_ascii_digit();
+        symbol |= !c.is_alphanumeric() && !c.is_whitespace();
+    }
+
+    if len < policy.min_length {
+        accum.add_failure(format!(
+            "must be at least {} characters long",
+            policy.min_length
+        ));
+    }
+    if let Some(max) = policy.max_length {
+        if len > max {
+            accum.add_failure(format!("must be at most {max} characters long"));
+        }
+    }
+    if policy.require_lowercase && !lowercase {
+        accum.add_failure("must contain a lowercase letter");
+    }
+    if policy.require_uppercase && !uppercase {
+        accum.add_failure("must contain an uppercase letter");
+    }
+    if policy.require_digit && !digit {
+        accum.add_failure("must contain a digit");
+    }
+    if policy.require_symbol && !symbol {
+        accum.add_failure("must contain a symbol");
+    }
+    if let Some(is_banned) = &policy.is_banned {
+        if is_banned(value) {
+            accum.add_failure("is too common");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::messages;
+
+    fn strict() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 10,
+            max_length: Some(20),
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            is_banned: Some(Box::new(|p| p == "Password123!")),
+        }
+    }
+
+    #[test]
+    fn default_policy() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(messages(|a| password(a, "hunter2", &policy)).len(), 1);
+        assert_eq!(messages(|a| password(a, "correct horse", &policy)).len(), 0);
+    }
+
+    #[test]
+    fn reports_every_criterion() {
+        assert_eq!(messages(|a| password(a, "", &strict())).len(), 5);
+        assert_eq!(messages(|a| password(a, "abc", &strict())).len(), 4);
+        assert_eq!(
+            messages(|a| password(a, "Tr0ub4dor&3xyz", &strict())).len(),
+            0
+        );
+        assert_eq!(
+            messages(|a| password(a, "Tr0ub4dor&3xyzTr0ub4dor&3xyz", &strict())).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn banned() {
+        assert_eq!(
+            messages(|a| password(a, "Password123!", &strict())).len(),
+            1
+        );
+    }
+}
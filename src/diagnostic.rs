@@ -0,0 +1,75 @@
+//! Integration with [miette](https://docs.rs/miette).
+use miette::Diagnostic;
+
+use crate::{Error, Failure, Severity};
+
+fn severity(severity: Severity) -> miette::Severity {
+    match severity {
+        Severity::Warning => miette::Severity::Warning,
+        Severity::Error => miette::Severity::Error,
+    }
+}
+
+/// The failures are listed by the error's [Display](core::fmt::Display) implementation,
+/// so they are not repeated as [related](Diagnostic::related) diagnostics.
+/// Each [Failure] is also a diagnostic in its own right.
+impl Diagnostic for Error {
+    fn severity(&self) -> Option<miette::Severity> {
+        let sev = self.iter().map(|f| f.severity).max().unwrap_or_default();
+        Some(severity(sev))
+    }
+}
+
+/// The [code](Failure::code), if any, is used as the diagnostic code.
+impl Diagnostic for Failure {
+    fn code<'a>(&'a self) -> Option<Box<dyn std::fmt::Display + 'a>> {
+        self.code
+            .as_deref()
+            .map(|c| Box::new(c) as Box<dyn std::fmt::Display>)
+    }
+
+    fn severity(&self) -> Option<miette::Severity> {
+        Some(severity(self.severity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Accumulator;
+
+    #[test]
+    fn diagnostic() {
+        let mut accum = Accumulator::new();
+        accum.add_failure_coded_at("name", "too_short", "must be at least 3 characters");
+        accum.add_warning_at("age", "is unusually high");
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(Diagnostic::severity(&err), Some(miette::Severity::Error));
+        assert!(err.related().is_none());
+        let failures = err.failures();
+        assert_eq!(failures[0].code().unwrap().to_string(), "too_short");
+        assert!(Diagnostic::code(&failures[1]).is_none());
+        assert_eq!(
+            Diagnostic::severity(&failures[1]),
+            Some(miette::Severity::Warning)
+        );
+    }
+
+    #[test]
+    fn report() {
+        let mut accum = Accumulator::new();
+        accum.add_failure_at("name", "must be at least 3 characters");
+        accum.add_warning_at("age", "is unusually high");
+        let err = crate::Result::from(accum).unwrap_err();
+        let mut out = String::new();
+        miette::NarratableReportHandler::new()
+            .render_report(&mut out, &err)
+            .unwrap();
+        assert_eq!(
+            out.matches("$.name: must be at least 3 characters").count(),
+            1
+        );
+        assert_eq!(out.matches("$.age (warning): is unusually high").count(), 1);
+        assert_eq!(out.matches("(warning)").count(), 1);
+    }
+}
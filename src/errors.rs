@@ -1,240 +1,2765 @@
-use std::fmt::{Display, Write};
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    sync::Arc,
+    vec,
+    vec::Vec,
+};
+#[cfg(not(feature = "std"))]
+pub(crate) use core::error::Error as StdError;
+use core::{
+    any::Any,
+    fmt::{Display, Write},
+};
+#[cfg(feature = "std")]
+pub(crate) use std::error::Error as StdError;
 
-use crate::Validate;
+use crate::{
+    localize::Translator,
+    render::{
+        DisplayOptions, DisplayOpts, DisplayWith, Dotted, JsonPath, PathRenderer, Tracked, Tree,
+    },
+    sink::{FailureSink, PendingFailure},
+    span::{Locate, Span},
+    Validate,
+};
 
-pub type Result<T = (), E = Error> = std::result::Result<T, E>;
+pub type Result<T = (), E = Error> = core::result::Result<T, E>;
 
-const INDENT: &str = "   ";
+pub(crate) const INDENT: &str = "   ";
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("Validation failure(s):")?;
-        for fa in self.0.iter() {
-            f.write_str("\n")?;
-            f.write_str(INDENT)?;
-            fa.fmt(f)?;
+/// With the `stacker` feature, the stack is grown if fewer than this many bytes remain
+/// before validating a member.
+#[cfg(feature = "stacker")]
+const STACK_RED_ZONE: usize = 64 * 1024;
+
+/// Size of each new stack segment allocated by the `stacker` feature.
+#[cfg(feature = "stacker")]
+const STACK_GROWTH: usize = 1024 * 1024;
+
+/// Number of keys which a [Path] can store without allocating.
+const INLINE_KEYS: usize = 6;
+
+/// Keys of a failure's path.
+///
+/// Short paths are stored inline to avoid allocating;
+/// longer ones are shared between failures recorded at the same prefix.
+#[derive(Clone)]
+pub(crate) enum Path {
+    Inline(smallvec::SmallVec<[Key; INLINE_KEYS]>),
+    Shared(Arc<[Key]>),
+}
+
+impl Path {
+    /// Add keys to the start of this path.
+    pub(crate) fn prepend(&mut self, prefix: &[Key]) {
+        if prefix.is_empty() {
+            return;
+        }
+        *self = prefix.iter().chain(self.iter()).cloned().collect();
+    }
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self::Inline(Default::default())
+    }
+}
+
+impl core::ops::Deref for Path {
+    type Target = [Key];
+
+    fn deref(&self) -> &[Key] {
+        match self {
+            Self::Inline(keys) => keys,
+            Self::Shared(keys) => keys,
+        }
+    }
+}
+
+impl From<&[Key]> for Path {
+    fn from(keys: &[Key]) -> Self {
+        if keys.len() <= INLINE_KEYS {
+            Self::Inline(keys.into())
+        } else {
+            Self::Shared(keys.into())
+        }
+    }
+}
+
+impl From<Vec<Key>> for Path {
+    fn from(keys: Vec<Key>) -> Self {
+        if keys.len() <= INLINE_KEYS {
+            Self::Inline(keys.into())
+        } else {
+            Self::Shared(keys.into())
+        }
+    }
+}
+
+impl FromIterator<Key> for Path {
+    fn from_iter<I: IntoIterator<Item = Key>>(iter: I) -> Self {
+        let keys: smallvec::SmallVec<[Key; INLINE_KEYS]> = iter.into_iter().collect();
+        if keys.spilled() {
+            Self::Shared(keys.into_vec().into())
+        } else {
+            Self::Inline(keys)
+        }
+    }
+}
+
+impl core::fmt::Debug for Path {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Path {}
+
+impl PartialOrd for Path {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Path {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl core::hash::Hash for Path {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+/// The prefix of an [Accumulator],
+/// whose [Path] is built when a failure there is first built, and shared by the rest.
+///
+/// Sinks which do not build failures never build the path.
+#[derive(Default)]
+pub(crate) struct Prefix {
+    keys: smallvec::SmallVec<[Key; INLINE_KEYS]>,
+    /// Built when first needed, and dropped when the keys change.
+    path: Option<Path>,
+}
+
+impl Prefix {
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub(crate) fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    fn push(&mut self, key: Key) {
+        self.path = None;
+        self.keys.push(key);
+    }
+
+    fn extend(&mut self, keys: &[Key]) {
+        if !keys.is_empty() {
+            self.path = None;
+            self.keys.extend(keys.iter().cloned());
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.keys.len() {
+            self.path = None;
+            self.keys.truncate(len);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub(crate) fn path(&mut self) -> &Path {
+        let keys = &self.keys;
+        self.path.get_or_insert_with(|| Path::from(&keys[..]))
+    }
+}
+
+/// Lists every failure on its own line.
+///
+/// With a precision, e.g. `{:.10}`, at most that many failures are shown,
+/// followed by a line counting those omitted.
+/// Use [Error::display_opts] for more control.
+///
+/// ```
+/// # use validatrix::{Accumulator, Result};
+/// let mut accum = Accumulator::new();
+/// for idx in 0..1000 {
+///     accum.add_failure_at(idx, "bad row");
+/// }
+/// let err = Result::from(accum).unwrap_err();
+/// assert_eq!(
+///     format!("{err:.2}"),
+///     "Validation failure(s):\n   $[0]: bad row\n   $[1]: bad row\n   ... and 998 more",
+/// );
+/// ```
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match f.precision() {
+            Some(max) => {
+                let options = DisplayOptions {
+                    max_failures: Some(max),
+                    ..Default::default()
+                };
+                self.display_opts(&options).fmt(f)
+            }
+            None => self.fmt_with(&Dotted, f),
         }
-        Ok(())
     }
 }
 
-impl std::error::Error for Error {}
+impl StdError for Error {}
 
 /// Validation error type wrapping a list of [Failure]s.
 #[derive(Debug)]
-pub struct Error(Vec<Failure>);
+pub struct Error(pub(crate) Vec<Failure>);
 
 impl Error {
-    #[allow(clippy::len_without_is_empty)]
+    /// Number of failures, including warnings.
     pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    /// Whether there are no failures, e.g. after they have been removed with [Self::retain].
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Keep only the failures for which the predicate returns `true`.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.add_failure_coded_at("a", "deprecated", "is deprecated");
+    /// accum.add_warning_at("b", "is unusual");
+    /// let mut err = Result::from(accum).unwrap_err();
+    ///
+    /// // this code is known to be benign
+    /// err.retain(|f| f.code() != Some("deprecated"));
+    /// assert_eq!(err.len(), 1);
+    /// // only a warning remains
+    /// assert!(err.into_result().is_ok());
+    /// ```
+    pub fn retain(&mut self, f: impl FnMut(&Failure) -> bool) {
+        self.0.retain(f)
+    }
+
+    /// Transform or remove each failure.
+    pub fn filter_map(self, f: impl FnMut(Failure) -> Option<Failure>) -> Self {
+        Self(self.0.into_iter().filter_map(f).collect())
+    }
+
+    /// Split into the failures for which the predicate returns `true`, and those for which it returns `false`.
+    ///
+    /// Either may be empty; use [Self::into_result] to check whether either contains errors.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.add_failure_coded_at("name", "too_short", "is too short");
+    /// accum.add_failure_coded_at("id", "internal", "could not be looked up");
+    /// let err = Result::from(accum).unwrap_err();
+    ///
+    /// let (internal, client) = err.split_by(|f| f.code() == Some("internal"));
+    /// assert_eq!(client.to_string(), "Validation failure(s):\n   $.name: is too short");
+    /// assert_eq!(internal.len(), 1);
+    /// ```
+    pub fn split_by(self, mut f: impl FnMut(&Failure) -> bool) -> (Self, Self) {
+        let (matched, unmatched) = self.0.into_iter().partition(|x| f(x));
+        (Self(matched), Self(unmatched))
+    }
+
+    /// Consume the error, returning the failures with [Severity::Error]
+    /// and those with [Severity::Warning] separately.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.add_failure_at("a", "bad");
+    /// accum.add_warning_at("b", "odd");
+    /// let (errors, warnings) = Result::from(accum).unwrap_err().partition_severity();
+    /// assert_eq!(errors[0].message(), "bad");
+    /// assert_eq!(warnings[0].message(), "odd");
+    /// ```
+    pub fn partition_severity(self) -> (Vec<Failure>, Vec<Failure>) {
+        self.0
+            .into_iter()
+            .partition(|f| f.severity == Severity::Error)
+    }
+
+    /// `Err` if there is at least one failure with [Severity::Error],
+    /// as when converting from an [Accumulator].
+    pub fn into_result(self) -> Result {
+        if self.0.iter().any(|f| f.severity == Severity::Error) {
+            Err(self)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Iterate over all failures, including warnings, in the order they were reported.
+    pub fn iter(&self) -> core::slice::Iter<'_, Failure> {
+        self.0.iter()
+    }
+
+    /// All failures, including warnings, in the order they were reported.
+    pub fn failures(&self) -> &[Failure] {
+        &self.0
+    }
+
+    /// Consume the error, returning its failures.
+    pub fn into_failures(self) -> Vec<Failure> {
+        self.0
+    }
+
+    /// Add the failures from another error after this error's failures.
+    ///
+    /// Useful for combining the results of validating several independent objects into one report.
+    ///
+    /// ```
+    /// # use validatrix::{Failure, Error};
+    /// let mut err = Error::from(Failure::new(&["a".into()], "bad a"));
+    /// err.merge(Error::from(Failure::new(&["b".into()], "bad b")));
+    /// err.merge_at("c", Error::from(Failure::new(&[0.into()], "bad c")));
+    /// assert_eq!(err.to_string(), "
+    /// Validation failure(s):
+    ///    $.a: bad a
+    ///    $.b: bad b
+    ///    $.c[0]: bad c
+    /// ".trim());
+    /// ```
+    pub fn merge(&mut self, other: Error) {
+        self.0.extend(other.0)
+    }
+
+    /// Like [Self::merge], but with the given key prepended to the paths of the other error's failures.
+    pub fn merge_at(&mut self, prefix: impl Into<Key>, mut other: Error) {
+        other.rebase(&[prefix.into()]);
+        self.merge(other)
+    }
+
+    /// Prepend the given keys to the paths of all failures,
+    /// e.g. to place an error from validating a sub-object within its parent.
+    pub fn rebase(&mut self, prefix: &[Key]) {
+        if prefix.is_empty() {
+            return;
+        }
+        for f in self.0.iter_mut() {
+            f.key.prepend(prefix);
+        }
+    }
+
+    /// Consume the error, returning the messages of failures with [Severity::Error]
+    /// keyed by their path, as expected by most form libraries.
+    ///
+    /// Paths are rendered like `b.cs[0].cvalue`, without the leading `$`;
+    /// failures at the root have the empty string as their key.
+    /// Warnings are omitted.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.add_failure_at("email", "must not be empty");
+    /// accum.add_failure_at("email", "must contain '@'");
+    /// accum.with_key("tags", |a| a.add_failure_at(1, "duplicate tag"));
+    /// accum.add_failure("passwords do not match");
+    /// let map = Result::from(accum).unwrap_err().into_field_map();
+    /// assert_eq!(map["email"], ["must not be empty", "must contain '@'"]);
+    /// assert_eq!(map["tags[1]"], ["duplicate tag"]);
+    /// assert_eq!(map[""], ["passwords do not match"]);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn into_field_map(self) -> std::collections::HashMap<String, Vec<String>> {
+        let mut map: std::collections::HashMap<_, Vec<_>> = Default::default();
+        for failure in self.0 {
+            if failure.severity != Severity::Error {
+                continue;
+            }
+            let mut path = String::new();
+            for (idx, key) in failure.key.iter().enumerate() {
+                match key {
+                    Key::Field(s) if idx == 0 => path.push_str(s),
+                    _ => path.push_str(&key.to_string()),
+                }
+            }
+            map.entry(path)
+                .or_default()
+                .push(failure.message.into_owned());
+        }
+        map
+    }
+
+    /// Remove repeated failures with the same key, message and severity,
+    /// keeping the first occurrence.
+    ///
+    /// Useful when the same invariant is violated via shared substructures.
+    pub fn dedup(&mut self) {
+        let mut seen = BTreeSet::new();
+        let keep: Vec<_> = self
+            .0
+            .iter()
+            .map(|f| seen.insert((&f.key, &f.message, f.severity)))
+            .collect();
+        let mut keep = keep.into_iter();
+        self.0.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// Compare against the error from a later run, e.g. after the input was edited.
+    ///
+    /// Failures are matched by path and [code](Failure::code), so a failure whose message
+    /// changed (e.g. to include a new value) is still considered the same.
+    /// Repeated failures with the same path and code are matched in order.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut before = Accumulator::new();
+    /// before.add_failure_coded_at("port", "range", "80 is privileged");
+    /// before.add_failure_at("name", "must not be empty");
+    /// let before = Result::from(before).unwrap_err();
+    ///
+    /// let mut after = Accumulator::new();
+    /// after.add_failure_coded_at("port", "range", "443 is privileged");
+    /// after.add_failure_at("host", "must not be empty");
+    /// let after = Result::from(after).unwrap_err();
+    ///
+    /// let diff = before.diff(&after);
+    /// assert_eq!(diff.appeared[0].message(), "must not be empty");
+    /// assert_eq!(diff.disappeared[0].json_path(), "$.name");
+    /// assert_eq!(diff.persisted[0].message(), "443 is privileged");
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Error) -> ErrorDiff<'a> {
+        let mut unmatched: BTreeMap<_, Vec<usize>> = BTreeMap::new();
+        for (idx, f) in self.0.iter().enumerate().rev() {
+            unmatched.entry((&f.key, f.code())).or_default().push(idx);
+        }
+        let mut matched = vec![false; self.0.len()];
+        let mut diff = ErrorDiff::default();
+        for f in other.iter() {
+            match unmatched.get_mut(&(&f.key, f.code())).and_then(Vec::pop) {
+                Some(idx) => {
+                    matched[idx] = true;
+                    diff.persisted.push(f);
+                }
+                None => diff.appeared.push(f),
+            }
+        }
+        diff.disappeared = self
+            .0
+            .iter()
+            .zip(matched)
+            .filter_map(|(f, m)| (!m).then_some(f))
+            .collect();
+        diff
+    }
+
+    /// Display this error, rendering failure paths with the given [PathRenderer].
+    ///
+    /// ```
+    /// use validatrix::{render::JsonPointer, Failure, Error};
+    ///
+    /// let err = Error::from(Failure::new(&["a".into(), 0.into()], "bad"));
+    /// assert_eq!(
+    ///     err.display_with(JsonPointer).to_string(),
+    ///     "Validation failure(s):\n   /a/0: bad",
+    /// );
+    /// ```
+    pub fn display_with<R: PathRenderer>(&self, renderer: R) -> DisplayWith<'_, Self, R> {
+        DisplayWith::new(self, renderer)
+    }
+
+    /// Attach [spans](Failure::span) to failures which do not already have one,
+    /// by finding their paths in the source text.
+    ///
+    /// See the [span](crate::span) module.
+    pub fn locate(&mut self, locator: &impl Locate) {
+        for failure in self.0.iter_mut().filter(|f| f.span.is_none()) {
+            failure.span = locator.locate(&failure.key);
+        }
+    }
+
+    /// Replace the messages of failures which the translator can localize.
+    ///
+    /// Failures it cannot translate keep their existing message.
+    /// See [Accumulator::add_failure_localized].
+    pub fn localize(&mut self, translator: &impl Translator) {
+        for failure in self.0.iter_mut() {
+            if let Some(message) = translator.translate(failure) {
+                failure.message = message.into();
+            }
+        }
+    }
+
+    /// Display this error with failures grouped under their common path prefixes,
+    /// which is easier to scan when many failures are under the same value.
+    ///
+    /// ```
+    /// use validatrix::{Accumulator, Result};
+    ///
+    /// let mut accum = Accumulator::new();
+    /// accum.with_key("servers", |a| a.with_key(3, |a| {
+    ///     a.add_failure_at("host", "must not be empty");
+    ///     a.add_failure_at("port", "must be positive");
+    /// }));
+    /// accum.add_failure_at("name", "must not be empty");
+    /// let err = Result::from(accum).unwrap_err();
+    /// assert_eq!(err.display_tree().to_string(), "
+    /// Validation failure(s):
+    ///    $
+    ///       .servers[3]
+    ///          .host: must not be empty
+    ///          .port: must be positive
+    ///       .name: must not be empty
+    /// ".trim());
+    /// ```
+    pub fn display_tree(&self) -> Tree<'_> {
+        Tree(self)
+    }
+
+    /// Display this error with colors, for printing to a terminal.
+    ///
+    /// Paths are highlighted and each failure is labelled with its [Severity].
+    /// Respects the `NO_COLOR` environment variable by default.
+    #[cfg(feature = "color")]
+    pub fn display_colored(&self) -> crate::render::Colored<'_> {
+        crate::render::Colored {
+            error: self,
+            enabled: None,
+        }
+    }
+
+    /// Display this error using the given [DisplayOptions].
+    pub fn display_opts<'a>(&'a self, options: &'a DisplayOptions) -> DisplayOpts<'a> {
+        DisplayOpts {
+            error: self,
+            options,
+        }
+    }
+
+    pub(crate) fn fmt_with(
+        &self,
+        renderer: &dyn PathRenderer,
+        f: &mut dyn Write,
+    ) -> core::fmt::Result {
+        f.write_str("Validation failure(s):")?;
+        for fa in self.0.iter() {
+            f.write_str("\n")?;
+            f.write_str(INDENT)?;
+            fa.fmt_with(renderer, f)?;
+        }
+        Ok(())
+    }
+
+    /// Iterate over failures with [Severity::Error].
+    pub fn errors(&self) -> impl Iterator<Item = &Failure> {
+        self.0.iter().filter(|f| f.severity == Severity::Error)
+    }
+
+    /// Iterate over failures with [Severity::Warning].
+    pub fn warnings(&self) -> impl Iterator<Item = &Failure> {
+        self.0.iter().filter(|f| f.severity == Severity::Warning)
+    }
+}
+
+/// Only produces an `Err` if there is at least one failure with [Severity::Error];
+/// warnings alone are discarded, unless [Accumulator::set_fail_on_warnings] is enabled.
+/// The `Err` includes all failures, including warnings.
+impl<S: FailureSink> From<Accumulator<S>> for Result<(), Error> {
+    fn from(mut value: Accumulator<S>) -> Self {
+        value.take_result()
+    }
+}
+
+/// Type used to build up a list of validation failures.
+///
+/// When validating fields of a struct while validating that struct,
+/// push the field's name (or index in a sequence)
+/// onto the `prefix` member of the accumulator
+/// before passing it in to the validator.
+/// This allows nested fields to report where the failure happened.
+///
+/// ```
+/// use validatrix::Accumulator;
+///
+/// fn accumulate(accum: &mut Accumulator) {
+///     accum.with_key("some_field", |a| if true == false { a.add_failure("pigs have flown") });
+///
+///     // equivalent to the above
+///     if true == false {
+///         accum.add_failure_at("some_field", "hell has frozen over");
+///     }
+/// }
+/// ```
+///
+/// By default, all failures are collected.
+/// Use [Accumulator::with_limit] or [Accumulator::fail_fast] to stop after a given number;
+/// further failures are ignored and the `validate_*` methods skip any remaining validation.
+///
+/// ```
+/// use validatrix::{Accumulator, FailureSink, Validate};
+///
+/// struct Even(u8);
+///
+/// impl Validate for Even {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 % 2 != 0 {
+///             accum.add_failure("value is odd");
+///         }
+///     }
+/// }
+///
+/// let values: Vec<_> = (0..100).map(Even).collect();
+/// let mut accum = Accumulator::with_limit(3);
+/// accum.validate_iter(&values);
+/// assert_eq!(accum.len(), 3);
+/// assert!(accum.is_full());
+/// ```
+///
+/// To do something other than collect failures (e.g. only count them),
+/// see [Accumulator::with_sink].
+/// The [FailureSink] is a type parameter,
+/// so [Validate] implementations should be generic over it.
+#[derive(Default)]
+pub struct Accumulator<S = Vec<Failure>> {
+    /// This prefix is applied to any failures added to the accumulator.
+    prefix: Prefix,
+    /// Receives every failure recorded.
+    sink: S,
+    /// Number of failures recorded, including any not kept by the sink.
+    n_failures: usize,
+    /// Number of failures with [Severity::Error].
+    n_errors: usize,
+    limit: Option<usize>,
+    max_depth: Option<usize>,
+    /// Addresses of shared values currently being validated, if cycle detection is enabled.
+    ancestors: Option<BTreeSet<usize>>,
+    /// Names of dynamic keys seen so far, if interning is enabled.
+    interned: Option<BTreeSet<Arc<str>>>,
+    fail_on_warnings: bool,
+}
+
+impl<S> core::fmt::Debug for Accumulator<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Accumulator")
+            .field("prefix", &self.prefix.keys)
+            .field("sink", &core::any::type_name::<S>())
+            .field("n_failures", &self.n_failures)
+            .field("n_errors", &self.n_errors)
+            .field("limit", &self.limit)
+            .field("max_depth", &self.max_depth)
+            .field("cycle_detection", &self.ancestors.is_some())
+            .field("interning", &self.interned.is_some())
+            .field("fail_on_warnings", &self.fail_on_warnings)
+            .finish()
+    }
+}
+
+impl Accumulator {
+    /// Create an accumulator which collects all failures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an accumulator which stops collecting failures after `limit` errors have been added.
+    ///
+    /// Warnings do not count towards the limit.
+    ///
+    /// # Panics
+    ///
+    /// If the limit is 0, as no failures could be collected and so every value would be valid.
+    pub fn with_limit(limit: usize) -> Self {
+        let mut accum = Self::default();
+        accum.set_limit(Some(limit));
+        accum
+    }
+
+    /// Create an accumulator which stops after the first failure.
+    pub fn fail_fast() -> Self {
+        Self::with_limit(1)
+    }
+
+    /// Create an accumulator which only counts failures and records where the first error was,
+    /// skipping the work of building messages and paths for every failure.
+    ///
+    /// The resulting [Error] has a single failure at the first error's path, summarising the count;
+    /// see [SummarySink](crate::sink::SummarySink).
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Even(u8);
+    ///
+    /// impl Validate for Even {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0 % 2 != 0 {
+    ///             accum.add_failure_fmt(format_args!("{} is odd", self.0));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let values: Vec<_> = (0..10).map(Even).collect();
+    /// let mut accum = Accumulator::counting();
+    /// values.validate_inner(&mut accum);
+    /// assert_eq!(
+    ///     accum.take_result().unwrap_err().to_string(),
+    ///     "Validation failure(s):\n   $[1]: 5 error(s)",
+    /// );
+    /// ```
+    pub fn counting() -> Accumulator<crate::sink::SummarySink> {
+        Accumulator::with_sink(crate::sink::SummarySink::default())
+    }
+
+    /// Create an accumulator which keeps up to `N` failures without allocating storage for them,
+    /// and counts any more; see [FixedSink](crate::sink::FixedSink).
+    ///
+    /// ```
+    /// # use validatrix::{sink::FixedSink, Accumulator, FailureSink, Validate};
+    /// struct Command {
+    ///     speed: u8,
+    ///     angle: i16,
+    /// }
+    ///
+    /// impl Validate for Command {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.speed > 100 {
+    ///             accum.add_failure_at("speed", "too fast");
+    ///         }
+    ///         if !(-90..=90).contains(&self.angle) {
+    ///             accum.add_failure_at("angle", "out of range");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut accum = Accumulator::fixed::<1>();
+    /// Command { speed: 120, angle: 180 }.validate_inner(&mut accum);
+    /// let sink = accum.sink();
+    /// assert_eq!(sink.failures()[0].message(), "too fast");
+    /// assert_eq!(sink.overflow(), 1);
+    /// ```
+    #[cfg(feature = "heapless")]
+    pub fn fixed<const N: usize>() -> Accumulator<crate::sink::FixedSink<N>> {
+        Accumulator::with_sink(crate::sink::FixedSink::<N>::new())
+    }
+
+    /// Create an accumulator with space for `capacity` failures before it needs to reallocate.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_sink(Vec::with_capacity(capacity))
+    }
+
+    /// The buffer which failures are collected in, for reusing its allocation.
+    #[cfg(feature = "std")]
+    pub(crate) fn buffer_mut(&mut self) -> &mut Vec<Failure> {
+        &mut self.sink
+    }
+
+    /// Iterate over the warnings logged by this accumulator.
+    ///
+    /// Useful for reporting warnings when validation otherwise succeeds,
+    /// as they are not included in an `Ok` result.
+    pub fn warnings(&self) -> impl Iterator<Item = &Failure> {
+        self.sink.iter().filter(|f| f.severity == Severity::Warning)
+    }
+}
+
+impl Accumulator<crate::sink::DiscardSink> {
+    /// Create an accumulator which only records whether there are any errors,
+    /// stopping at the first.
+    pub(crate) fn validity() -> Self {
+        let mut accum = Self::with_sink(crate::sink::DiscardSink);
+        accum.set_limit(Some(1));
+        accum
+    }
+}
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Create an accumulator which passes failures to the given [FailureSink]
+    /// rather than collecting them.
+    ///
+    /// Only failures kept by the sink are included in the resulting [Error].
+    /// See the [sink](crate::sink) module for some implementations.
+    pub fn with_sink(sink: S) -> Self {
+        Self {
+            prefix: Prefix::default(),
+            sink,
+            n_failures: 0,
+            n_errors: 0,
+            limit: None,
+            max_depth: None,
+            ancestors: None,
+            interned: None,
+            fail_on_warnings: false,
+        }
+    }
+
+    /// The accumulator's sink.
+    ///
+    /// ```
+    /// # use validatrix::Accumulator;
+    /// let mut accum = Accumulator::counting();
+    /// accum.add_failure_at("a", "bad");
+    /// accum.add_warning("iffy");
+    /// let summary = accum.sink();
+    /// assert_eq!((summary.errors(), summary.warnings()), (1, 1));
+    /// ```
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Mutable access to the accumulator's sink.
+    pub fn sink_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Take the accumulator's sink, discarding the rest of its state.
+    pub fn into_sink(self) -> S {
+        self.sink
+    }
+
+    /// Set or remove the limit on the number of errors collected (see [Accumulator::with_limit]).
+    ///
+    /// # Panics
+    ///
+    /// If the limit is 0.
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        assert_ne!(limit, Some(0), "accumulator limit must be at least 1");
+        self.limit = limit;
+    }
+
+    /// Set or remove the maximum depth of keys which members can be validated at.
+    ///
+    /// Members which would be deeper than this are not validated;
+    /// instead, a failure is added at the member's path.
+    /// This protects against stack overflows when validating deeply nested (possibly malicious) input,
+    /// as long as nested members are validated with methods like [Accumulator::validate_member_at],
+    /// [Accumulator::validate_iter] and [Accumulator::validate_map].
+    ///
+    /// Alternatively, the `stacker` feature grows the stack on the heap as those methods descend,
+    /// so that arbitrarily deep input can be validated without a depth limit.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Node(Vec<Node>);
+    ///
+    /// impl Validate for Node {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.validate_iter_at("children", &self.0);
+    ///     }
+    /// }
+    ///
+    /// let deep = (0..10).fold(Node(vec![]), |n, _| Node(vec![n]));
+    /// let mut accum = Accumulator::new();
+    /// accum.set_max_depth(Some(4));
+    /// deep.validate_inner(&mut accum);
+    /// assert_eq!(
+    ///     validatrix::Result::from(accum).unwrap_err().to_string(),
+    ///     "Validation failure(s):\n   $.children[0].children[0].children: maximum validation depth exceeded",
+    /// );
+    /// ```
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    /// Enable or disable detection of cycles in graphs of shared values.
+    ///
+    /// When enabled, [Rc](std::rc::Rc) and [Arc](std::sync::Arc) (or any type using [Accumulator::guard_cycle])
+    /// record their address while their contents are validated.
+    /// Reaching the same value again while it is still being validated adds a failure
+    /// rather than validating it forever.
+    /// Values which are shared but not part of a cycle are validated every time they are reached.
+    pub fn set_cycle_detection(&mut self, enabled: bool) {
+        self.ancestors = enabled.then(BTreeSet::new);
+    }
+
+    /// Enable or disable interning of dynamic key names.
+    ///
+    /// When validating many similar values (e.g. rows of a table),
+    /// the same field and map key names are created again and again,
+    /// and every failure under them keeps its own copy.
+    /// When enabled, each distinct name is only allocated once per accumulator:
+    /// keys added to the prefix are replaced by an earlier equal name,
+    /// map keys are formatted into a reused buffer and only allocated if they are new,
+    /// and [Accumulator::intern] reuses earlier names.
+    ///
+    /// [Forks](Self::fork) start with an empty set of names,
+    /// so that they can run in parallel without locking;
+    /// names from their failures are replaced by this accumulator's when they are [merged](Self::merge).
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Row(BTreeMap<String, i64>);
+    ///
+    /// impl Validate for Row {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.with_key("cells", |a| {
+    ///             for (column, value) in &self.0 {
+    ///                 if *value < 0 {
+    ///                     // `add_failure_at(column.clone(), ..)` would also be interned,
+    ///                     // but only after allocating a new copy of the name
+    ///                     let column = a.intern(column);
+    ///                     a.add_failure_at(column, "is negative");
+    ///                 }
+    ///             }
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// let rows: Vec<_> = (0..1000)
+    ///     .map(|_| Row([("balance".to_string(), -1)].into_iter().collect()))
+    ///     .collect();
+    /// let mut accum = Accumulator::new();
+    /// accum.set_interning(true);
+    /// rows.validate_inner(&mut accum);
+    /// assert_eq!(accum.len(), 1000);
+    /// ```
+    pub fn set_interning(&mut self, enabled: bool) {
+        self.interned = enabled.then(BTreeSet::new);
+    }
+
+    /// Get a shared copy of a dynamic key name.
+    ///
+    /// If interning is enabled (see [Accumulator::set_interning]),
+    /// this reuses the allocation of an equal name seen earlier by this accumulator.
+    pub fn intern(&mut self, name: &str) -> KeyStr {
+        let Some(interned) = self.interned.as_mut() else {
+            return KeyStr::from(Arc::<str>::from(name));
+        };
+        if let Some(existing) = interned.get(name) {
+            return KeyStr(KeyStrRepr::Shared(Arc::clone(existing)));
+        }
+        let shared: Arc<str> = Arc::from(name);
+        interned.insert(Arc::clone(&shared));
+        KeyStr(KeyStrRepr::Shared(shared))
+    }
+
+    /// Replace a dynamic key's name with an interned one, if interning is enabled.
+    fn intern_key(&mut self, key: Key) -> Key {
+        let Some(interned) = self.interned.as_mut() else {
+            return key;
+        };
+        let mut canonical = |s: KeyStr| match s.0 {
+            KeyStrRepr::Shared(shared) => match interned.get(&shared) {
+                Some(existing) => KeyStr(KeyStrRepr::Shared(Arc::clone(existing))),
+                None => {
+                    interned.insert(Arc::clone(&shared));
+                    KeyStr(KeyStrRepr::Shared(shared))
+                }
+            },
+            repr => KeyStr(repr),
+        };
+        match key {
+            Key::Field(s) => Key::Field(canonical(s)),
+            Key::MapKey(s) => Key::MapKey(canonical(s)),
+            key => key,
+        }
+    }
+
+    /// Whether warnings alone should produce an `Err` from [Self::take_result]
+    /// (and converting into a [Result]); `false` by default.
+    ///
+    /// Useful for "strict" modes, e.g. in CI.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.set_fail_on_warnings(true);
+    /// accum.add_warning("deprecated option");
+    /// assert!(Result::from(accum).is_err());
+    /// ```
+    pub fn set_fail_on_warnings(&mut self, enabled: bool) {
+        self.fail_on_warnings = enabled;
+    }
+
+    /// Run the given closure to validate the value at `ptr`,
+    /// unless cycle detection is enabled and that value is already being validated.
+    ///
+    /// Useful for implementing [Validate] on custom shared pointer types.
+    pub fn guard_cycle<T: ?Sized>(&mut self, ptr: *const T, f: impl FnOnce(&mut Self)) {
+        let addr = ptr as *const () as usize;
+        match self.ancestors.as_mut() {
+            None => f(self),
+            Some(ancestors) => {
+                if !ancestors.insert(addr) {
+                    self.add_failure("cycle detected");
+                    return;
+                }
+                f(self);
+                if let Some(ancestors) = self.ancestors.as_mut() {
+                    ancestors.remove(&addr);
+                }
+            }
+        }
+    }
+
+    /// Create an empty accumulator with this one's configuration,
+    /// for validating a member separately and then adding its failures back with [Accumulator::merge_at].
+    ///
+    /// This allows members to be validated concurrently, e.g. by async validators,
+    /// which cannot all hold `&mut` references to the same accumulator.
+    /// The limit is reduced by the number of errors already collected,
+    /// and the maximum depth accounts for the current prefix and the key the member will be merged at.
+    /// A custom sink is not shared; failures reach it when they are merged back.
+    ///
+    /// ```
+    /// use validatrix::{asynch::Validate, Accumulator, FailureSink};
+    ///
+    /// struct Name(String);
+    ///
+    /// impl Validate for Name {
+    ///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0.is_empty() {
+    ///             accum.add_failure("must not be empty");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// struct Person {
+    ///     first: Name,
+    ///     last: Name,
+    /// }
+    ///
+    /// impl Validate for Person {
+    ///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         let (mut first, mut last) = (accum.fork(), accum.fork());
+    ///         futures::join!(
+    ///             self.first.validate_inner(&mut first),
+    ///             self.last.validate_inner(&mut last),
+    ///         );
+    ///         accum.merge_at("first", first);
+    ///         accum.merge_at("last", last);
+    ///     }
+    /// }
+    ///
+    /// let person = Person { first: Name("".into()), last: Name("".into()) };
+    /// let err = futures::executor::block_on(person.validate()).unwrap_err();
+    /// assert_eq!(err.len(), 2);
+    /// ```
+    pub fn fork(&self) -> Accumulator {
+        self.forker()()
+    }
+
+    /// Make a function which creates [forks](Self::fork) of this accumulator,
+    /// which can be shared between threads.
+    pub(crate) fn forker(&self) -> impl Fn() -> Accumulator + Send + Sync {
+        let limit = self.limit.map(|l| l.saturating_sub(self.n_errors));
+        let max_depth = self
+            .max_depth
+            .map(|d| d.saturating_sub(self.prefix.len() + 1));
+        let ancestors = self.ancestors.clone();
+        let interning = self.interned.is_some();
+        let fail_on_warnings = self.fail_on_warnings;
+        move || Accumulator {
+            limit,
+            max_depth,
+            ancestors: ancestors.clone(),
+            interned: interning.then(BTreeSet::new),
+            fail_on_warnings,
+            ..Default::default()
+        }
+    }
+
+    /// Whether members cannot be validated below the current prefix because of the maximum depth.
+    pub(crate) fn at_max_depth(&self) -> bool {
+        self.max_depth.is_some_and(|d| self.prefix.len() >= d)
+    }
+
+    /// Discard all failures and prefixes, keeping allocated memory and the limit (if any).
+    pub fn reset(&mut self) {
+        self.prefix.clear();
+        self.sink.truncate(&Checkpoint::default());
+        self.n_failures = 0;
+        self.n_errors = 0;
+    }
+
+    /// Produce a result from the failures collected so far and [reset](Self::reset) the accumulator.
+    ///
+    /// Unlike converting the accumulator `into` a [Result],
+    /// this allows one accumulator (and its allocations) to be reused
+    /// for validating many values.
+    /// Valid values then do not need any allocation.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Even(u8);
+    ///
+    /// impl Validate for Even {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0 % 2 != 0 {
+    ///             accum.add_failure("value is odd");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut accum = Accumulator::with_capacity(4);
+    /// let n_valid = (0..100)
+    ///     .map(Even)
+    ///     .filter(|v| {
+    ///         v.validate_inner(&mut accum);
+    ///         accum.take_result().is_ok()
+    ///     })
+    ///     .count();
+    /// assert_eq!(n_valid, 50);
+    /// ```
+    pub fn take_result(&mut self) -> Result {
+        let fails = if self.fail_on_warnings {
+            self.n_failures > 0
+        } else {
+            self.n_errors > 0
+        };
+        let result = if fails {
+            Err(Error(self.sink.take_failures()))
+        } else {
+            Ok(())
+        };
+        self.reset();
+        result
+    }
+
+    /// Add an extra failure to this accumulator.
+    ///
+    /// Ignored if the accumulator [is full](Self::is_full).
+    pub fn add_failure(&mut self, message: impl Into<Cow<'static, str>>) {
+        let mut message = Some(message);
+        self.record(Severity::Error, &mut |path| {
+            Failure::new_at(
+                path.clone(),
+                message.take().map(Into::into).unwrap_or_default(),
+            )
+        })
+    }
+
+    /// Pass a failure to the sink, respecting the limit.
+    ///
+    /// `build` is given the current prefix and is only called if the failure needs to be built.
+    fn record(&mut self, severity: Severity, build: &mut dyn FnMut(&Path) -> Failure) {
+        if self.is_full() {
+            return;
+        }
+        self.n_failures += 1;
+        if severity == Severity::Error {
+            self.n_errors += 1;
+        }
+        self.sink.record(PendingFailure {
+            prefix: &mut self.prefix,
+            severity,
+            build,
+        });
+    }
+
+    /// Add an extra failure whose message is only formatted if the failure is kept.
+    ///
+    /// Sinks which do not build failures (e.g. [CountingSink](crate::sink::CountingSink))
+    /// skip the formatting entirely.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Port(u16);
+    ///
+    /// impl Validate for Port {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0 < 1024 {
+    ///             accum.add_failure_fmt(format_args!("port {} is privileged", self.0));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let err = Port(80).validate().unwrap_err();
+    /// assert_eq!(err.to_string(), "Validation failure(s):\n   $: port 80 is privileged");
+    /// ```
+    pub fn add_failure_fmt(&mut self, message: core::fmt::Arguments<'_>) {
+        self.record(Severity::Error, &mut |path| {
+            Failure::new_at(path.clone(), message.to_string())
+        })
+    }
+
+    /// Like [Self::add_failure_fmt], at the given key.
+    pub fn add_failure_fmt_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        message: core::fmt::Arguments<'_>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        self.with_key(prefix, |a| a.add_failure_fmt(message))
+    }
+
+    /// Add an extra failure whose message is only built if the failure is kept,
+    /// for messages which are expensive to construct.
+    pub fn add_failure_lazy(&mut self, message: impl FnOnce() -> String) {
+        let mut message = Some(message);
+        self.record(Severity::Error, &mut |path| {
+            Failure::new_at(
+                path.clone(),
+                message.take().map(|m| m()).unwrap_or_default(),
+            )
+        })
+    }
+
+    /// Like [Self::add_failure_lazy], at the given key.
+    pub fn add_failure_lazy_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        message: impl FnOnce() -> String,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        self.with_key(prefix, |a| a.add_failure_lazy(message))
+    }
+
+    /// Add an extra failure with an attached payload,
+    /// which application code can retrieve with [Failure::payload].
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// #[derive(Debug, PartialEq)]
+    /// struct Conflict {
+    ///     existing_id: u64,
+    /// }
+    ///
+    /// struct NewUser {
+    ///     email: String,
+    /// }
+    ///
+    /// impl Validate for NewUser {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         // pretend to look this up in a database
+    ///         if self.email == "taken@example.com" {
+    ///             accum.with_key("email", |a| {
+    ///                 a.add_failure_with_payload("email in use", Conflict { existing_id: 42 })
+    ///             });
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let err = NewUser { email: "taken@example.com".into() }.validate().unwrap_err();
+    /// let failure = err.errors().next().unwrap();
+    /// assert_eq!(failure.payload(), Some(&Conflict { existing_id: 42 }));
+    /// ```
+    pub fn add_failure_with_payload(
+        &mut self,
+        message: impl Into<Cow<'static, str>>,
+        payload: impl Any + Send + Sync,
+    ) {
+        let mut message = Some(message);
+        let mut payload = Some(payload);
+        self.record(Severity::Error, &mut |path| {
+            let f = Failure::new_at(
+                path.clone(),
+                message.take().map(Into::into).unwrap_or_default(),
+            );
+            match payload.take() {
+                Some(p) => f.with_payload(p),
+                None => f,
+            }
+        })
+    }
+
+    /// Add an extra failure with a machine-readable [code](Failure::code),
+    /// which frontends can branch on or translate rather than relying on the message.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Username(String);
+    ///
+    /// impl Validate for Username {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0.len() < 3 {
+    ///             accum.add_failure_coded("too_short", "must be at least 3 characters");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let err = Username("ab".into()).validate().unwrap_err();
+    /// assert_eq!(err.errors().next().unwrap().code(), Some("too_short"));
+    /// ```
+    pub fn add_failure_coded(
+        &mut self,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        let mut code = Some(code);
+        let mut message = Some(message);
+        self.record(Severity::Error, &mut |path| {
+            let f = Failure::new_at(
+                path.clone(),
+                message.take().map(Into::into).unwrap_or_default(),
+            );
+            match code.take() {
+                Some(c) => f.with_code(c),
+                None => f,
+            }
+        })
+    }
+
+    /// Like [Self::add_failure_coded], at the given key.
+    pub fn add_failure_coded_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        self.with_key(prefix, |a| a.add_failure_coded(code, message))
+    }
+
+    /// Add an extra failure which can be [localized](Error::localize),
+    /// with a [code](Failure::code) identifying the message
+    /// and [parameters](Failure::params) to interpolate into it.
+    ///
+    /// The given message is used if the failure is not localized.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Username(String);
+    ///
+    /// impl Validate for Username {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0.len() < 3 {
+    ///             accum.add_failure_localized(
+    ///                 "too-short",
+    ///                 [("min", 3)],
+    ///                 "must be at least 3 characters",
+    ///             );
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut err = Username("ab".into()).validate().unwrap_err();
+    /// err.localize(&|f: &validatrix::Failure| match f.code()? {
+    ///     "too-short" => Some(format!("doit comporter au moins {} caractères", f.param("min")?)),
+    ///     _ => None,
+    /// });
+    /// assert_eq!(err.to_string(), "Validation failure(s):\n   $: doit comporter au moins 3 caractères");
+    /// ```
+    pub fn add_failure_localized<V: Display>(
+        &mut self,
+        code: impl Into<Cow<'static, str>>,
+        params: impl IntoIterator<Item = (&'static str, V)>,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        let mut code = Some(code);
+        let mut params = Some(params);
+        let mut message = Some(message);
+        self.record(Severity::Error, &mut |path| {
+            let mut f = Failure::new_at(
+                path.clone(),
+                message.take().map(Into::into).unwrap_or_default(),
+            );
+            if let Some(c) = code.take() {
+                f = f.with_code(c);
+            }
+            for (name, value) in params.take().into_iter().flatten() {
+                f = f.with_param(name, value);
+            }
+            f
+        })
+    }
+
+    /// Add a failure caused by some other error (e.g. from parsing a field).
+    ///
+    /// The error's `Display` representation is used as the message,
+    /// and the error itself is kept as the failure's [source](Failure::source).
+    ///
+    /// ```
+    /// # use std::num::ParseIntError;
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Config {
+    ///     threads: String,
+    /// }
+    ///
+    /// impl Validate for Config {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if let Err(e) = self.threads.parse::<u8>() {
+    ///             accum.add_error_at("threads", e);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let err = Config { threads: "many".into() }.validate().unwrap_err();
+    /// let failure = err.errors().next().unwrap();
+    /// assert_eq!(failure.to_string(), "$.threads: invalid digit found in string");
+    /// assert!(failure.source().unwrap().downcast_ref::<ParseIntError>().is_some());
+    /// ```
+    pub fn add_error(&mut self, error: impl StdError + Send + Sync + 'static) {
+        let mut error = Some(error);
+        self.record(Severity::Error, &mut |path| match error.take() {
+            Some(e) => Failure::new_at(path.clone(), e.to_string()).with_source(e),
+            None => Failure::new_at(path.clone(), ""),
+        })
+    }
+
+    /// Like [Self::add_error], at the given key.
+    pub fn add_error_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        error: impl StdError + Send + Sync + 'static,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        self.with_key(prefix, |a| a.add_error(error))
+    }
+
+    /// Accumulate an extra failure at the given key.
+    pub fn add_failure_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        self.with_key(prefix, |a| a.add_failure(message))
+    }
+
+    /// Add a failure if `condition` is false.
+    pub fn ensure(&mut self, condition: bool, message: impl Into<Cow<'static, str>>) {
+        if !condition {
+            self.add_failure(message)
+        }
+    }
+
+    /// Add a failure at the given key if `condition` is false.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Range {
+    ///     min: u8,
+    ///     max: u8,
+    /// }
+    ///
+    /// impl Validate for Range {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.ensure_at("max", self.max >= self.min, "max is less than min");
+    ///     }
+    /// }
+    ///
+    /// assert!(Range { min: 2, max: 1 }.validate().is_err());
+    /// ```
+    pub fn ensure_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        condition: bool,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        if !condition {
+            self.add_failure_at(prefix, message)
+        }
+    }
+
+    /// Like [Self::ensure], but also returns `condition`.
+    ///
+    /// Useful for skipping checks which depend on this one.
+    pub fn require(&mut self, condition: bool, message: impl Into<Cow<'static, str>>) -> bool {
+        self.ensure(condition, message);
+        condition
+    }
+
+    /// Like [Self::ensure_at], but also returns `condition`.
+    ///
+    /// Useful for skipping checks which depend on this one.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Page {
+    ///     items: Vec<u8>,
+    ///     selected: usize,
+    /// }
+    ///
+    /// impl Validate for Page {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if accum.require_at("items", !self.items.is_empty(), "no items") {
+    ///             accum.ensure_at("selected", self.selected < self.items.len(), "out of range");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Page { items: vec![], selected: 1 }.validate().unwrap_err().len(), 1);
+    /// ```
+    pub fn require_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        condition: bool,
+        message: impl Into<Cow<'static, str>>,
+    ) -> bool {
+        self.ensure_at(prefix, condition, message);
+        condition
+    }
+
+    /// Add a warning to this accumulator.
+    ///
+    /// Warnings are reported alongside errors, but do not by themselves cause validation to fail.
+    /// Ignored if the accumulator [is full](Self::is_full).
+    pub fn add_warning(&mut self, message: impl Into<Cow<'static, str>>) {
+        if self.is_full() {
+            return;
+        }
+        let mut message = Some(message);
+        self.record(Severity::Warning, &mut |path| {
+            Failure::new_at(
+                path.clone(),
+                message.take().map(Into::into).unwrap_or_default(),
+            )
+            .with_severity(Severity::Warning)
+        })
+    }
+
+    /// Accumulate a warning at the given key.
+    pub fn add_warning_at(
+        &mut self,
+        prefix: impl Into<Key>,
+        message: impl Into<Cow<'static, str>>,
+    ) {
+        if self.is_full() {
+            return;
+        }
+        self.with_key(prefix, |a| a.add_warning(message))
+    }
+
+    /// Accumulate any validation errors for a [Validate] field with key `field`.
+    ///
+    /// Skipped if the accumulator [is full](Self::is_full).
+    pub fn validate_member_at<V: Validate + ?Sized>(&mut self, field: impl Into<Key>, member: &V) {
+        self.descend(field, |a| member.validate_inner(a))
+    }
+
+    /// Like [Self::validate_member_at], but for a [crate::ValidateContext] field with the given context.
+    pub fn validate_member_at_ctx<T: crate::ValidateContext + ?Sized>(
+        &mut self,
+        field: impl Into<Key>,
+        member: &T,
+        context: &T::Context,
+    ) {
+        self.descend(field, |a| member.validate_inner_ctx(a, context))
+    }
+
+    /// Validate a member at the given key, unless the accumulator is full
+    /// or the key would exceed the maximum depth.
+    fn descend(&mut self, field: impl Into<Key>, f: impl FnOnce(&mut Self)) {
+        if self.is_full() {
+            return;
+        }
+        if self.at_max_depth() {
+            self.add_failure_at(field, "maximum validation depth exceeded");
+            return;
+        }
+        #[cfg(feature = "stacker")]
+        stacker::maybe_grow(STACK_RED_ZONE, STACK_GROWTH, || self.with_key(field, f));
+        #[cfg(not(feature = "stacker"))]
+        self.with_key(field, f)
+    }
+
+    /// Accumulate any validation errors for an optional [Validate] field with key `field`.
+    ///
+    /// `None` is valid.
+    pub fn validate_option_at<V: Validate>(&mut self, field: impl Into<Key>, member: &Option<V>) {
+        if let Some(m) = member {
+            self.validate_member_at(field, m)
+        }
+    }
+
+    /// Like [Self::validate_option_at], but for a [crate::ValidateContext] field with the given context.
+    pub fn validate_option_at_ctx<T: crate::ValidateContext>(
+        &mut self,
+        field: impl Into<Key>,
+        member: &Option<T>,
+        context: &T::Context,
+    ) {
+        if let Some(m) = member {
+            self.validate_member_at_ctx(field, m, context)
+        }
+    }
+
+    /// Accumulate any validation errors for a [Validate] field with key `field` which must be present.
+    ///
+    /// `None` is reported as a failure.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Name(String);
+    ///
+    /// impl Validate for Name {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.ensure(!self.0.is_empty(), "empty name");
+    ///     }
+    /// }
+    ///
+    /// struct Person {
+    ///     name: Option<Name>,
+    ///     nickname: Option<Name>,
+    /// }
+    ///
+    /// impl Validate for Person {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.required_at("name", &self.name);
+    ///         accum.validate_option_at("nickname", &self.nickname);
+    ///     }
+    /// }
+    ///
+    /// let person = Person { name: None, nickname: Some(Name(String::new())) };
+    /// assert_eq!(
+    ///     person.validate().unwrap_err().to_string(),
+    ///     "Validation failure(s):
+    ///    $.name: missing required value
+    ///    $.nickname: empty name",
+    /// );
+    /// ```
+    pub fn required_at<V: Validate>(&mut self, field: impl Into<Key>, member: &Option<V>) {
+        match member {
+            Some(m) => self.validate_member_at(field, m),
+            None => self.add_failure_at(field, "missing required value"),
+        }
+    }
+
+    /// Like [Self::required_at], but for a [crate::ValidateContext] field with the given context.
+    pub fn required_at_ctx<T: crate::ValidateContext>(
+        &mut self,
+        field: impl Into<Key>,
+        member: &Option<T>,
+        context: &T::Context,
+    ) {
+        match member {
+            Some(m) => self.validate_member_at_ctx(field, m, context),
+            None => self.add_failure_at(field, "missing required value"),
+        }
+    }
+
+    /// Perform manual validation inside the given closure at the given key, only if `condition` is true.
+    ///
+    /// Useful for rules which only apply in certain modes.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// #[derive(PartialEq)]
+    /// enum Kind {
+    ///     Local,
+    ///     Remote,
+    /// }
+    ///
+    /// struct Source {
+    ///     kind: Kind,
+    ///     url: Option<String>,
+    /// }
+    ///
+    /// impl Validate for Source {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.validate_if(self.kind == Kind::Remote, "url", |a| {
+    ///             a.ensure(self.url.is_some(), "remote sources need a URL")
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// assert!(Source { kind: Kind::Local, url: None }.validate().is_ok());
+    /// assert!(Source { kind: Kind::Remote, url: None }.validate().is_err());
+    /// ```
+    pub fn validate_if(
+        &mut self,
+        condition: bool,
+        prefix: impl Into<Key>,
+        f: impl FnOnce(&mut Self),
+    ) {
+        if condition {
+            self.with_key(prefix, f)
+        }
+    }
+
+    /// Accumulate any validation errors for a [Validate] field with key `field`,
+    /// only if `condition` is true.
+    pub fn validate_member_if<V: Validate + ?Sized>(
+        &mut self,
+        condition: bool,
+        field: impl Into<Key>,
+        member: &V,
+    ) {
+        if condition {
+            self.validate_member_at(field, member)
+        }
+    }
+
+    /// Perform manual validation inside the given closure for a member with the given prefix.
+    ///
+    /// The closure takes an accumulator as an argument,
+    /// which will be this accumulator with the added prefix.
+    pub fn with_key(&mut self, prefix: impl Into<Key>, f: impl FnOnce(&mut Self)) {
+        self.at(prefix, f)
+    }
+
+    /// Like [Self::with_key], but returns the closure's return value.
+    ///
+    /// The prefix is removed however the closure exits (including by panicking),
+    /// so the closure can use `?` and early returns freely.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Config {
+    ///     port: String,
+    /// }
+    ///
+    /// impl Validate for Config {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         let _port: Option<u16> = accum.at("port", |a| {
+    ///             let Ok(port) = self.port.parse::<u16>() else {
+    ///                 a.add_failure("not a port number");
+    ///                 return None;
+    ///             };
+    ///             if port < 1024 {
+    ///                 a.add_failure("privileged port");
+    ///             }
+    ///             Some(port)
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// assert!(Config { port: "80".into() }.validate().is_err());
+    /// assert!(Config { port: "8080".into() }.validate().is_ok());
+    /// ```
+    pub fn at<R>(&mut self, prefix: impl Into<Key>, f: impl FnOnce(&mut Self) -> R) -> R {
+        let key = self.intern_key(prefix.into());
+        self.prefix.push(key);
+        let guard = PrefixGuard { accum: self, n: 1 };
+        f(guard.accum)
+    }
+
+    /// Add all of the failures from another accumulator to this one,
+    /// with this accumulator's current prefix prepended to their keys.
+    ///
+    /// Useful for combining the results of validation done in stages or in parallel.
+    /// This accumulator's limit, if any, still applies.
+    ///
+    /// If the other accumulator has a custom sink, only failures kept by that sink are added.
+    pub fn merge<T: FailureSink>(&mut self, mut other: Accumulator<T>) {
+        for failure in other.sink.take_failures() {
+            if self.is_full() {
+                return;
+            }
+            let mut failure = failure;
+            if self.interned.is_some() {
+                failure.key = failure
+                    .key
+                    .iter()
+                    .map(|k| self.intern_key(k.clone()))
+                    .collect();
+            }
+            let severity = failure.severity;
+            let mut failure = Some(failure);
+            self.record(severity, &mut |path| {
+                let mut f = failure.take().expect("failure is only built once");
+                f.key.prepend(path);
+                f
+            });
+        }
+    }
+
+    /// Like [Self::merge], but with the given key added to the prefix.
+    pub fn merge_at<T: FailureSink>(&mut self, prefix: impl Into<Key>, other: Accumulator<T>) {
+        self.with_key(prefix, |a| a.merge(other))
+    }
+
+    /// Record the current state of the accumulator, so that failures added later can be discarded.
+    ///
+    /// Useful for trying alternative interpretations of a value.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// fn check_literal<S: FailureSink>(value: &str, accum: &mut Accumulator<S>) {
+    ///     if value.parse::<u32>().is_err() {
+    ///         accum.add_failure("not a number");
+    ///     }
+    /// }
+    ///
+    /// fn check_template<S: FailureSink>(value: &str, accum: &mut Accumulator<S>) {
+    ///     if !value.starts_with("{{") || !value.ends_with("}}") {
+    ///         accum.add_failure("not a template");
+    ///     }
+    /// }
+    ///
+    /// struct Setting(String);
+    ///
+    /// impl Validate for Setting {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         let cp = accum.checkpoint();
+    ///         check_literal(&self.0, accum);
+    ///         if accum.errors_since(&cp) == 0 {
+    ///             return;
+    ///         }
+    ///         accum.rollback_to(cp);
+    ///         check_template(&self.0, accum);
+    ///     }
+    /// }
+    ///
+    /// assert!(Setting("3".into()).validate().is_ok());
+    /// assert!(Setting("{{ n_threads }}".into()).validate().is_ok());
+    /// assert_eq!(Setting("three".into()).validate().unwrap_err().len(), 1);
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.n_failures,
+            n_errors: self.n_errors,
+        }
+    }
+
+    /// Discard any failures added since the given [Checkpoint] was created.
+    ///
+    /// Does nothing if failures have already been discarded past this checkpoint.
+    /// Custom sinks may not be able to discard failures; see [FailureSink::truncate].
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) {
+        if checkpoint.len > self.n_failures {
+            return;
+        }
+        self.sink.truncate(&checkpoint);
+        self.n_failures = checkpoint.len;
+        self.n_errors = checkpoint.n_errors;
+    }
+
+    /// Number of failures with [Severity::Error] added since the given [Checkpoint] was created.
+    pub fn errors_since(&self, checkpoint: &Checkpoint) -> usize {
+        self.n_errors.saturating_sub(checkpoint.n_errors)
+    }
+
+    /// Convenience method for [Accumulator::with_key]-like behaviour at multiple keys' depth.
+    pub fn with_keys(&mut self, prefixes: &[Key], f: impl FnOnce(&mut Self)) {
+        self.prefix.extend(prefixes);
+        let guard = PrefixGuard {
+            accum: self,
+            n: prefixes.len(),
+        };
+        f(guard.accum)
+    }
+
+    /// Iterate over a collection of [Validate]-able items,
+    /// validating them all in turn.
+    /// As this tracks the items' index in the iterable,
+    /// the whole collection should be passed rather than a filtered version.
+    pub fn validate_iter<'a, V: Validate + ?Sized + 'a, I: IntoIterator<Item = &'a V>>(
+        &mut self,
+        items: I,
+    ) {
+        for (idx, item) in items.into_iter().enumerate() {
+            if self.is_full() {
+                return;
+            }
+            self.validate_member_at(idx, item);
+        }
+    }
+
+    /// Like [Self::validate_iter], but for a collection of [crate::ValidateContext] items with the given context.
+    pub fn validate_iter_ctx<
+        'a,
+        V: crate::ValidateContext + ?Sized + 'a,
+        I: IntoIterator<Item = &'a V>,
+    >(
+        &mut self,
+        items: I,
+        context: &V::Context,
+    ) {
+        for (idx, item) in items.into_iter().enumerate() {
+            if self.is_full() {
+                return;
+            }
+            self.validate_member_at_ctx(idx, item, context);
+        }
+    }
+
+    /// Convenience method to do [Self::validate_iter] for a given key.
+    pub fn validate_iter_at<'a, V: Validate + ?Sized + 'a, I: IntoIterator<Item = &'a V>>(
+        &mut self,
+        prefix: impl Into<Key>,
+        items: I,
+    ) {
+        self.descend(prefix, |a| a.validate_iter(items));
+    }
+
+    /// Like [Self::validate_iter_at], but for a collection of [crate::ValidateContext] items with the given context.
+    pub fn validate_iter_at_ctx<
+        'a,
+        V: crate::ValidateContext + ?Sized + 'a,
+        I: IntoIterator<Item = &'a V>,
+    >(
+        &mut self,
+        prefix: impl Into<Key>,
+        items: I,
+        context: &V::Context,
+    ) {
+        self.descend(prefix, |a| a.validate_iter_ctx(items, context));
+    }
+
+    /// Iterate over the entries of a map whose values are [Validate]-able,
+    /// validating each value at a [Key::MapKey] rendered from its key.
+    pub fn validate_map<'a, K, V, I>(&mut self, items: I)
+    where
+        K: Display + ?Sized + 'a,
+        V: Validate + ?Sized + 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.validate_map_with_keys(items, |_, _| {})
+    }
+
+    /// Convenience method to do [Self::validate_map] for a given key.
+    pub fn validate_map_at<'a, K, V, I>(&mut self, prefix: impl Into<Key>, items: I)
+    where
+        K: Display + ?Sized + 'a,
+        V: Validate + ?Sized + 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.descend(prefix, |a| a.validate_map(items))
+    }
+
+    /// Like [Self::validate_map], but also check each key with the given closure.
+    ///
+    /// The closure's accumulator has the entry's key as its prefix.
+    ///
+    /// ```
+    /// # use std::collections::BTreeMap;
+    /// # use validatrix::{Accumulator, FailureSink, Validate};
+    /// struct Port(u16);
+    ///
+    /// impl Validate for Port {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.ensure(self.0 != 0, "port must be non-zero");
+    ///     }
+    /// }
+    ///
+    /// struct Services(BTreeMap<String, Port>);
+    ///
+    /// impl Validate for Services {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.validate_map_with_keys_at("services", &self.0, |a, k| {
+    ///             a.ensure(k.chars().all(|c| c.is_ascii_lowercase()), "name must be lowercase")
+    ///         });
+    ///     }
+    /// }
+    ///
+    /// let services = Services([("Web".to_string(), Port(0))].into_iter().collect());
+    /// assert_eq!(
+    ///     services.validate().unwrap_err().to_string(),
+    ///     r#"Validation failure(s):
+    ///    $.services["Web"]: name must be lowercase
+    ///    $.services["Web"]: port must be non-zero"#,
+    /// );
+    /// ```
+    pub fn validate_map_with_keys<'a, K, V, I>(
+        &mut self,
+        items: I,
+        mut check_key: impl FnMut(&mut Self, &K),
+    ) where
+        K: Display + ?Sized + 'a,
+        V: Validate + ?Sized + 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        let mut name = String::new();
+        for (k, v) in items {
+            if self.is_full() {
+                return;
+            }
+            let key = if self.interned.is_some() {
+                name.clear();
+                let _ = write!(name, "{k}");
+                Key::MapKey(self.intern(&name))
+            } else {
+                Key::map_key(k)
+            };
+            self.descend(key, |a| {
+                check_key(a, k);
+                v.validate_inner(a);
+            });
+        }
+    }
+
+    /// Convenience method to do [Self::validate_map_with_keys] for a given key.
+    pub fn validate_map_with_keys_at<'a, K, V, I>(
+        &mut self,
+        prefix: impl Into<Key>,
+        items: I,
+        check_key: impl FnMut(&mut Self, &K),
+    ) where
+        K: Display + ?Sized + 'a,
+        V: Validate + ?Sized + 'a,
+        I: IntoIterator<Item = (&'a K, &'a V)>,
+    {
+        self.descend(prefix, |a| a.validate_map_with_keys(items, check_key))
+    }
+
+    /// Number of failures logged by this accumulator, including warnings.
+    pub fn len(&self) -> usize {
+        self.n_failures
+    }
+
+    /// Whether this accumulator has any failures with [Severity::Error].
+    pub fn has_errors(&self) -> bool {
+        self.n_errors > 0
+    }
+
+    /// Whether this accumulator has 0 failures.
+    pub fn is_empty(&self) -> bool {
+        self.n_failures == 0
+    }
+
+    /// Whether this accumulator has reached its failure limit, if it has one,
+    /// or its sink will not accept any more failures.
+    pub fn is_full(&self) -> bool {
+        self.limit.is_some_and(|l| self.n_errors >= l) || self.sink.is_full()
+    }
+}
+
+/// State of an [Accumulator] which can be returned to with [Accumulator::rollback_to].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    len: usize,
+    n_errors: usize,
+}
+
+impl Checkpoint {
+    /// Number of failures recorded before the checkpoint, including warnings.
+    pub fn failures(&self) -> usize {
+        self.len
+    }
+
+    /// Number of failures with [Severity::Error] recorded before the checkpoint.
+    pub fn errors(&self) -> usize {
+        self.n_errors
+    }
+
+    /// Number of failures with [Severity::Warning] recorded before the checkpoint.
+    pub fn warnings(&self) -> usize {
+        self.len - self.n_errors
+    }
+}
+
+/// Removes the last `n` keys from the accumulator's prefix when dropped.
+struct PrefixGuard<'a, S> {
+    accum: &'a mut Accumulator<S>,
+    n: usize,
+}
+
+impl<S> Drop for PrefixGuard<'_, S> {
+    fn drop(&mut self) {
+        let len = self.accum.prefix.len().saturating_sub(self.n);
+        self.accum.prefix.truncate(len);
+    }
+}
+
+/// Struct representing a single validation failure.
+/// Used to build informative error messages for [Error].
+#[derive(Debug)]
+pub struct Failure {
+    pub(crate) key: Path,
+    pub(crate) message: Cow<'static, str>,
+    pub(crate) severity: Severity,
+    pub(crate) code: Option<Cow<'static, str>>,
+    pub(crate) params: Vec<(Cow<'static, str>, String)>,
+    pub(crate) payload: Option<Box<dyn Any + Send + Sync>>,
+    pub(crate) source: Option<BoxedError>,
+    pub(crate) span: Option<Span>,
+}
+
+/// Boxed error stored as the source of a [Failure].
+pub type BoxedError = Box<dyn StdError + Send + Sync>;
+
+impl Failure {
+    pub fn new(path: &[Key], msg: impl Into<Cow<'static, str>>) -> Self {
+        Self::new_at(Path::from(path), msg)
+    }
+
+    /// Like [Failure::new], reusing an existing path.
+    pub(crate) fn new_at(path: Path, msg: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            key: path,
+            message: msg.into(),
+            severity: Severity::Error,
+            code: None,
+            params: Vec::new(),
+            payload: None,
+            source: None,
+            span: None,
+        }
+    }
+
+    /// Attach an error which caused this failure, replacing any existing source.
+    ///
+    /// See [Accumulator::add_error].
+    pub fn with_source(mut self, source: impl Into<BoxedError>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// The error which caused this failure, if any.
+    ///
+    /// Use [downcast_ref](https://doc.rust-lang.org/std/error/trait.Error.html#method.downcast_ref)
+    /// to recover the original error type.
+    pub fn source(&self) -> Option<&(dyn StdError + Send + Sync + 'static)> {
+        self.source.as_deref()
+    }
+
+    /// Attach an arbitrary payload to this failure, replacing any existing payload.
+    ///
+    /// See [Accumulator::add_failure_with_payload].
+    pub fn with_payload(mut self, payload: impl Any + Send + Sync) -> Self {
+        self.payload = Some(Box::new(payload));
+        self
+    }
+
+    /// The payload attached to this failure, if it has one of type `T`.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.as_ref()?.downcast_ref()
+    }
+
+    /// Set the severity of this failure.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// How serious this failure is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Set a machine-readable code for this failure, like `"too_short"`.
+    ///
+    /// See [Accumulator::add_failure_coded].
+    pub fn with_code(mut self, code: impl Into<Cow<'static, str>>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// The machine-readable code for this failure, if any.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Add a named parameter, for interpolating into a [localized](Error::localize) message.
+    ///
+    /// See [Accumulator::add_failure_localized].
+    pub fn with_param(mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        self.params.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// The named parameters of this failure, in the order they were added.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.params.iter().map(|(k, v)| (k.as_ref(), v.as_str()))
+    }
+
+    /// The value of the named parameter, if present.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params().find(|(k, _)| *k == name).map(|(_, v)| v)
+    }
+
+    /// Set the location of the failing value in the source text.
+    ///
+    /// See [Error::locate].
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// The location of the failing value in the source text, if known.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// Path to this failure.
+    pub fn keys(&self) -> &[Key] {
+        &self.key
+    }
+
+    /// Description of this failure.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Path to this failure as a JSONPath ([RFC 9535](https://www.rfc-editor.org/rfc/rfc9535)) query,
+    /// e.g. `$.b.cs[0].cvalue`.
+    ///
+    /// Unlike the [Display] implementation, field names which are not valid JSONPath identifiers
+    /// are written in quoted bracket notation, e.g. `$["content-type"]`.
+    pub fn json_path(&self) -> String {
+        let mut out = String::new();
+        // writing to a String cannot fail
+        let _ = JsonPath.render(&self.key, &mut out);
+        out
+    }
+}
+
+impl<T: Into<Cow<'static, str>>> From<T> for Failure {
+    fn from(value: T) -> Self {
+        Self::new(&[], value)
+    }
+}
+
+/// Changes between two [Error]s, created by [Error::diff].
+#[derive(Debug, Clone, Default)]
+pub struct ErrorDiff<'a> {
+    /// Failures only in the later error.
+    pub appeared: Vec<&'a Failure>,
+    /// Failures only in the earlier error.
+    pub disappeared: Vec<&'a Failure>,
+    /// Failures in both errors, as they are in the later error.
+    pub persisted: Vec<&'a Failure>,
+}
+
+impl ErrorDiff<'_> {
+    /// Whether no failures appeared or disappeared.
+    pub fn is_unchanged(&self) -> bool {
+        self.appeared.is_empty() && self.disappeared.is_empty()
+    }
+}
+
+/// How serious a [Failure] is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Advisory only; does not cause validation to fail.
+    Warning,
+    /// Causes validation to fail.
+    #[default]
+    Error,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_with(&Dotted, f)
+    }
+}
+
+impl Failure {
+    /// Display this failure, rendering its path with the given [PathRenderer].
+    pub fn display_with<R: PathRenderer>(&self, renderer: R) -> DisplayWith<'_, Self, R> {
+        DisplayWith::new(self, renderer)
+    }
+
+    pub(crate) fn fmt_with(
+        &self,
+        renderer: &dyn PathRenderer,
+        f: &mut dyn Write,
+    ) -> core::fmt::Result {
+        let mut path = Tracked::new(f);
+        renderer.render(&self.key, &mut path)?;
+        let sep = if path.written { " " } else { "" };
+        if self.severity == Severity::Warning {
+            write!(f, "{sep}(warning): ")?;
+        } else if path.written {
+            f.write_str(": ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// The [source](Failure::source) of the failure, if any, is the error's source.
+impl StdError for Failure {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_deref()
+            .map(|e| e as &(dyn StdError + 'static))
+    }
+}
+
+impl From<Failure> for Error {
+    /// N.B. this produces an error even if the failure is a warning.
+    fn from(value: Failure) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl IntoIterator for Error {
+    type Item = Failure;
+    type IntoIter = alloc::vec::IntoIter<Failure>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Error {
+    type Item = &'a Failure;
+    type IntoIter = core::slice::Iter<'a, Failure>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// N.B. this produces an error even if there are no failures, or only warnings;
+/// see [Error::into_result].
+///
+/// ```
+/// # use validatrix::{Accumulator, Error, Result, Severity};
+/// let mut accum = Accumulator::new();
+/// accum.add_failure_at("a", "bad");
+/// accum.add_warning_at("b", "odd");
+/// let err: Error = Result::from(accum)
+///     .unwrap_err()
+///     .into_iter()
+///     .filter(|f| f.severity() == Severity::Error)
+///     .collect();
+/// assert_eq!(err.to_string(), "Validation failure(s):\n   $.a: bad");
+/// ```
+impl FromIterator<Failure> for Error {
+    fn from_iter<I: IntoIterator<Item = Failure>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Failure> for Error {
+    fn extend<I: IntoIterator<Item = Failure>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
 }
 
-impl From<Accumulator> for Result<(), Error> {
-    fn from(value: Accumulator) -> Self {
-        if value.failures.is_empty() {
-            Ok(())
-        } else {
-            Err(Error(value.failures))
-        }
-    }
+/// One component of the path to a failure.
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Index into a sequence, displayed like `[0]`.
+    Index(usize),
+    /// Name of a struct field, displayed like `.field`.
+    ///
+    /// Usually a string literal, but can be shared for names only known at runtime
+    /// (e.g. from a dynamic schema).
+    Field(KeyStr),
+    /// Key of a map entry, displayed like `["key"]`.
+    ///
+    /// This distinguishes map entries from struct fields with the same name,
+    /// e.g. `$.env["PATH"]` vs `$.env.PATH`.
+    MapKey(KeyStr),
 }
 
-/// Type used to build up a list of validation failures.
+/// String held by a [Key]; either a `&'static str` or a reference-counted `Arc<str>`.
 ///
-/// When validating fields of a struct while validating that struct,
-/// push the field's name (or index in a sequence)
-/// onto the `prefix` member of the accumulator
-/// before passing it in to the validator.
-/// This allows nested fields to report where the failure happened.
+/// Keys are cloned into every [Failure] under them,
+/// so this makes clones cheap even for names only known at runtime.
+/// To avoid allocating the same name repeatedly,
+/// create it once and clone it into each key:
 ///
 /// ```
-/// use validatrix::Accumulator;
-///
-/// fn accumulate(accum: &mut Accumulator) {
-///     accum.with_key("some_field", |a| if true == false { a.add_failure("pigs have flown") });
+/// use validatrix::{Accumulator, Key, KeyStr};
 ///
-///     // equivalent to the above
-///     if true == false {
-///         accum.add_failure_at("some_field", "hell has frozen over");
-///     }
+/// let column = KeyStr::from(String::from("user_id"));
+/// let mut accum = Accumulator::new();
+/// for row in 0..3 {
+///     accum.add_failure_at(row, "bad row");
+///     accum.with_key(row, |a| a.add_failure_at(column.clone(), "bad id"));
 /// }
+/// assert_eq!(accum.len(), 6);
 /// ```
-#[derive(Debug)]
-pub struct Accumulator {
-    /// This prefix is applied to any failures added to the accumulator.
-    prefix: Vec<Key>,
-    failures: Vec<Failure>,
+#[derive(Clone)]
+pub struct KeyStr(KeyStrRepr);
+
+#[derive(Clone)]
+enum KeyStrRepr {
+    Static(&'static str),
+    Shared(Arc<str>),
 }
 
-impl Accumulator {
-    pub(crate) fn new() -> Self {
-        Self {
-            prefix: Default::default(),
-            failures: Default::default(),
+impl KeyStr {
+    /// Wrap a static string without allocating.
+    pub const fn from_static(s: &'static str) -> Self {
+        Self(KeyStrRepr::Static(s))
+    }
+
+    /// The string.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            KeyStrRepr::Static(s) => s,
+            KeyStrRepr::Shared(s) => s,
+        }
+    }
+
+    /// The string, if it was static.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn as_static(&self) -> Option<&'static str> {
+        match &self.0 {
+            KeyStrRepr::Static(s) => Some(s),
+            KeyStrRepr::Shared(_) => None,
         }
     }
 }
 
-impl Accumulator {
-    /// Add an extra failure to this accumulator.
-    pub fn add_failure(&mut self, message: impl Into<String>) {
-        self.failures.push(Failure::new(&self.prefix, message))
+impl core::ops::Deref for KeyStr {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
     }
+}
 
-    /// Accumulate an extra failure at the given key.
-    pub fn add_failure_at(&mut self, prefix: impl Into<Key>, message: impl Into<String>) {
-        self.with_key(prefix, |a| a.add_failure(message))
+impl AsRef<str> for KeyStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
     }
+}
 
-    /// Accumulate any validation errors for a [Validate] field with key `field`.
-    pub fn validate_member_at(&mut self, field: impl Into<Key>, member: &impl Validate) {
-        self.with_key(field, |a| member.validate_inner(a))
+impl core::borrow::Borrow<str> for KeyStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
     }
+}
 
-    /// Like [Self::validate_member_at], but for a [crate::ValidateContext] field with the given context.
-    pub fn validate_member_at_ctx<T: crate::ValidateContext>(
-        &mut self,
-        field: impl Into<Key>,
-        member: &T,
-        context: &T::Context,
-    ) {
-        self.with_key(field, |a| member.validate_inner_ctx(a, context))
+impl core::fmt::Debug for KeyStr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
     }
+}
 
-    /// Perform manual validation inside the given closure for a member with the given prefix.
-    ///
-    /// The closure takes an accumulator as an argument,
-    /// which will be this accumulator with the added prefix.
-    pub fn with_key(&mut self, prefix: impl Into<Key>, f: impl FnOnce(&mut Self)) {
-        self.prefix.push(prefix.into());
-        f(self);
-        self.prefix.pop();
+impl Display for KeyStr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self)
     }
+}
 
-    /// Convenience method for [Accumulator::with_key]-like behaviour at multiple keys' depth.
-    pub fn with_keys(&mut self, prefixes: &[Key], f: impl FnOnce(&mut Self)) {
-        let len = prefixes.len();
-        for p in prefixes {
-            self.prefix.push(*p);
-        }
-        f(self);
-        for _ in 0..len {
-            self.prefix.pop();
-        }
+impl PartialEq for KeyStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
     }
+}
 
-    /// Iterate over a collection of [Validate]-able items,
-    /// validating them all in turn.
-    /// As this tracks the items' index in the iterable,
-    /// the whole collection should be passed rather than a filtered version.
-    pub fn validate_iter<'a, V: Validate + 'a, I: IntoIterator<Item = &'a V>>(&mut self, items: I) {
-        items.into_iter().enumerate().for_each(|(idx, item)| {
-            self.validate_member_at(idx, item);
-        })
+impl Eq for KeyStr {}
+
+impl PartialEq<str> for KeyStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
     }
+}
 
-    /// Like [Self::validate_iter], but for a collection of [crate::ValidateContext] items with the given context.
-    pub fn validate_iter_ctx<'a, V: crate::ValidateContext + 'a, I: IntoIterator<Item = &'a V>>(
-        &mut self,
-        items: I,
-        context: &V::Context,
-    ) {
-        items.into_iter().enumerate().for_each(|(idx, item)| {
-            self.validate_member_at_ctx(idx, item, context);
-        })
+impl PartialEq<&str> for KeyStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
     }
+}
 
-    /// Convenience method to do [Self::validate_iter] for a given key.
-    pub fn validate_iter_at<'a, V: Validate + 'a, I: IntoIterator<Item = &'a V>>(
-        &mut self,
-        prefix: impl Into<Key>,
-        items: I,
-    ) {
-        self.with_key(prefix, |a| a.validate_iter(items));
+impl PartialOrd for KeyStr {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
     }
+}
 
-    /// Like [Self::validate_iter_at], but for a collection of [crate::ValidateContext] items with the given context.
-    pub fn validate_iter_at_ctx<
-        'a,
-        V: crate::ValidateContext + 'a,
-        I: IntoIterator<Item = &'a V>,
-    >(
-        &mut self,
-        prefix: impl Into<Key>,
-        items: I,
-        context: &V::Context,
-    ) {
-        self.with_key(prefix, |a| a.validate_iter_ctx(items, context));
+impl Ord for KeyStr {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
     }
+}
 
-    /// Number of failures logged by this accumulator.
-    pub fn len(&self) -> usize {
-        self.failures.len()
+impl core::hash::Hash for KeyStr {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
     }
+}
 
-    /// Whether this accumulator has 0 failures.
-    pub fn is_empty(&self) -> bool {
-        self.failures.is_empty()
+impl From<&'static str> for KeyStr {
+    fn from(value: &'static str) -> Self {
+        Self::from_static(value)
     }
 }
 
-/// Struct representing a single validation failure.
-/// Used to build informative error messages for [Error].
-#[derive(Debug)]
-pub struct Failure {
-    pub(crate) key: Vec<Key>,
-    // todo: replace with Cow?
-    pub(crate) message: String,
+impl From<String> for KeyStr {
+    fn from(value: String) -> Self {
+        Self(KeyStrRepr::Shared(value.into()))
+    }
 }
 
-impl Failure {
-    pub fn new(path: &[Key], msg: impl Into<String>) -> Self {
-        Self {
-            key: path.to_vec(),
-            message: msg.into(),
+impl From<Arc<str>> for KeyStr {
+    fn from(value: Arc<str>) -> Self {
+        Self(KeyStrRepr::Shared(value))
+    }
+}
+
+impl From<Cow<'static, str>> for KeyStr {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(s) => s.into(),
+            Cow::Owned(s) => s.into(),
         }
     }
 }
 
-impl<T: Into<String>> From<T> for Failure {
-    fn from(value: T) -> Self {
-        Self {
-            key: Default::default(),
-            message: value.into(),
+impl Key {
+    /// Create a [Key::MapKey] from anything which can be displayed.
+    pub fn map_key(key: impl Display) -> Self {
+        Self::MapKey(key.to_string().into())
+    }
+
+    /// Parse a path like `b.cs[0].cvalue` into its keys.
+    ///
+    /// Accepts the formats produced by [Failure]'s [Display] implementation and [Failure::json_path]:
+    /// an optional leading `$`, `.field` (the first `.` may be omitted), `[0]`,
+    /// and `["key"]` with JSON string escapes.
+    /// Quoted keys are parsed as [Key::MapKey].
+    /// Bare field names may not contain `.`, `[`, `]` or `"`.
+    ///
+    /// ```
+    /// use validatrix::Key;
+    ///
+    /// let keys = Key::parse_path("b.cs[0].cvalue").unwrap();
+    /// assert_eq!(keys, vec![Key::from("b"), "cs".into(), 0.into(), "cvalue".into()]);
+    /// assert_eq!(Key::parse_path(r#"$.env["PATH"]"#).unwrap()[1], Key::map_key("PATH"));
+    /// assert!(Key::parse_path("b..c").is_err());
+    /// ```
+    pub fn parse_path(path: &str) -> core::result::Result<Vec<Self>, ParsePathError> {
+        PathParser {
+            input: path,
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// Write this key as a JSONPath segment.
+    pub(crate) fn write_json_path(&self, f: &mut dyn Write) -> core::fmt::Result {
+        match self {
+            Key::Field(s) if is_json_path_name(s) => {
+                f.write_char('.')?;
+                f.write_str(s)
+            }
+            Key::Field(s) => write_bracketed(f, s),
+            Key::MapKey(s) => write_bracketed(f, s),
+            Key::Index(n) => f.write_fmt(format_args!("[{n}]")),
         }
     }
 }
 
-impl Display for Failure {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_char('$')?;
-        for c in self.key.iter() {
+/// Error from [Key::parse_path].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePathError {
+    position: usize,
+    message: &'static str,
+}
+
+impl ParsePathError {
+    /// Byte offset into the input at which parsing failed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParsePathError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "invalid path at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl StdError for ParsePathError {}
+
+struct PathParser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl PathParser<'_> {
+    fn err(&self, message: &'static str) -> ParsePathError {
+        ParsePathError {
+            position: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn expect(
+        &mut self,
+        expected: char,
+        message: &'static str,
+    ) -> core::result::Result<(), ParsePathError> {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.err(message))
+        }
+    }
+
+    fn parse(mut self) -> core::result::Result<Vec<Key>, ParsePathError> {
+        let mut keys = Vec::new();
+        if self.peek() == Some('$') {
+            self.pos += 1;
+        } else if !matches!(self.peek(), None | Some('[' | '.')) {
+            keys.push(self.field()?);
+        }
+        while let Some(c) = self.next() {
             match c {
-                Key::Index(n) => f.write_fmt(format_args!("[{n}]"))?,
-                Key::Field(s) => {
-                    f.write_char('.')?;
-                    f.write_str(s)?;
+                '.' => keys.push(self.field()?),
+                '[' => {
+                    keys.push(self.bracketed()?);
+                    self.expect(']', "expected ']'")?;
+                }
+                _ => {
+                    self.pos -= c.len_utf8();
+                    return Err(self.err("expected '.' or '['"));
                 }
             }
         }
-        f.write_str(": ")?;
-        f.write_str(&self.message)
+        Ok(keys)
+    }
+
+    fn field(&mut self) -> core::result::Result<Key, ParsePathError> {
+        let rest = &self.input[self.pos..];
+        let len = rest.find(['.', '[']).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.err("expected field name"));
+        }
+        if let Some(bad) = rest[..len].find([']', '"']) {
+            self.pos += bad;
+            return Err(self.err("unexpected character in field name"));
+        }
+        self.pos += len;
+        Ok(Key::from(rest[..len].to_string()))
+    }
+
+    fn bracketed(&mut self) -> core::result::Result<Key, ParsePathError> {
+        match self.peek() {
+            Some('"') => {
+                self.pos += 1;
+                self.quoted().map(|s| Key::MapKey(s.into()))
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let rest = &self.input[self.pos..];
+                let len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let idx = rest[..len]
+                    .parse()
+                    .map_err(|_| self.err("index out of range"))?;
+                self.pos += len;
+                Ok(Key::Index(idx))
+            }
+            _ => Err(self.err("expected index or quoted key")),
+        }
+    }
+
+    /// Parse the remainder of a JSON string, after the opening quote.
+    fn quoted(&mut self) -> core::result::Result<String, ParsePathError> {
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.err("unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => {
+                    let c = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        Some(c) => {
+                            self.pos -= c.len_utf8();
+                            return Err(self.err("invalid escape"));
+                        }
+                        None => {
+                            self.pos -= 1;
+                            return Err(self.err("invalid escape"));
+                        }
+                    };
+                    out.push(c);
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> core::result::Result<u32, ParsePathError> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .filter(|d| d.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| self.err("expected 4 hex digits"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("checked hex digits"))
+    }
+
+    fn unicode_escape(&mut self) -> core::result::Result<char, ParsePathError> {
+        let mut code = self.hex4()?;
+        if (0xD800..0xDC00).contains(&code) {
+            // high surrogate, which must be followed by an escaped low surrogate
+            if !self.input[self.pos..].starts_with("\\u") {
+                return Err(self.err("expected low surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.err("expected low surrogate"));
+            }
+            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+        }
+        char::from_u32(code).ok_or_else(|| self.err("invalid unicode escape"))
     }
 }
 
-impl From<Failure> for Error {
-    fn from(value: Failure) -> Self {
-        Self(vec![value])
+/// Whether the name can be used in JSONPath's dot shorthand notation.
+fn is_json_path_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    (first.is_ascii_alphabetic() || first == '_' || !first.is_ascii())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii())
+}
+
+/// Displays as the component of a path, e.g. `.field`, `[0]`, or `["key"]`.
+impl Display for Key {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Key::Index(n) => f.write_fmt(format_args!("[{n}]")),
+            Key::Field(s) => {
+                f.write_char('.')?;
+                f.write_str(s)
+            }
+            Key::MapKey(s) => write_bracketed(f, s),
+        }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq)]
-pub enum Key {
-    Index(usize),
-    // todo: also CoW?
-    Field(&'static str),
+/// Write a string in quoted bracket notation, like `["key"]`.
+pub(crate) fn write_bracketed(f: &mut dyn Write, s: &str) -> core::fmt::Result {
+    f.write_char('[')?;
+    write_quoted(f, s)?;
+    f.write_char(']')
+}
+
+/// Write a string surrounded by double quotes, escaped as in JSON.
+pub(crate) fn write_quoted(f: &mut dyn Write, s: &str) -> core::fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => f.write_fmt(format_args!("\\u{:04x}", c as u32))?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
 }
 
 impl From<usize> for Key {
@@ -245,6 +2770,408 @@ impl From<usize> for Key {
 
 impl From<&'static str> for Key {
     fn from(value: &'static str) -> Self {
+        Self::Field(value.into())
+    }
+}
+
+impl From<String> for Key {
+    fn from(value: String) -> Self {
+        Self::Field(value.into())
+    }
+}
+
+impl From<Cow<'static, str>> for Key {
+    fn from(value: Cow<'static, str>) -> Self {
+        Self::Field(value.into())
+    }
+}
+
+impl From<Arc<str>> for Key {
+    fn from(value: Arc<str>) -> Self {
+        Self::Field(value.into())
+    }
+}
+
+impl From<KeyStr> for Key {
+    fn from(value: KeyStr) -> Self {
         Self::Field(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lazy_prefix_path() {
+        let mut accum = Accumulator::with_sink(crate::sink::CountingSink::default());
+        accum.at("a", |a| {
+            a.add_failure("bad");
+            a.add_warning("iffy");
+            assert!(a.prefix.path.is_none());
+        });
+        let mut accum = Accumulator::new();
+        accum.at("a", |a| {
+            a.add_failure("bad");
+            assert!(a.prefix.path.is_some());
+        });
+    }
+
+    #[test]
+    fn map_key_display() {
+        let mut accum = Accumulator::new();
+        accum.with_key("env", |a| {
+            a.add_failure_at("PATH", "field");
+            a.add_failure_at(Key::map_key("PATH"), "map entry");
+            a.add_failure_at(Key::map_key("say \"hi\"\n\u{7}"), "escaped");
+            a.add_failure_at(Key::map_key("ünïcødé"), "unicode");
+        });
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"Validation failure(s):
+   $.env.PATH: field
+   $.env["PATH"]: map entry
+   $.env["say \"hi\"\n\u0007"]: escaped
+   $.env["ünïcødé"]: unicode"#
+        );
+    }
+
+    #[test]
+    fn json_path() {
+        let f = Failure::new(&["b".into(), "cs".into(), 0.into(), "cvalue".into()], "msg");
+        assert_eq!(f.json_path(), "$.b.cs[0].cvalue");
+
+        let f = Failure::new(
+            &[
+                "headers".into(),
+                String::from("content-type").into(),
+                String::from("").into(),
+                "_ok9".into(),
+                String::from("9lives").into(),
+                Key::map_key("say \"hi\""),
+            ],
+            "msg",
+        );
+        assert_eq!(
+            f.json_path(),
+            r#"$.headers["content-type"][""]._ok9["9lives"]["say \"hi\""]"#
+        );
+
+        assert_eq!(Failure::new(&[], "msg").json_path(), "$");
+    }
+
+    #[test]
+    fn parse_path_roundtrip() {
+        let keys = vec![
+            Key::from("b"),
+            String::from("content-type").into(),
+            0.into(),
+            Key::map_key("say \"hi\"\n\u{7}"),
+            Key::map_key("ünïcødé"),
+            "cvalue".into(),
+        ];
+        let f = Failure::new(&keys, "msg");
+        let display = f.to_string();
+        let display = display.strip_suffix(": msg").unwrap();
+        assert_eq!(Key::parse_path(display).unwrap(), keys);
+        assert_eq!(Key::parse_path("").unwrap(), vec![]);
+        assert_eq!(Key::parse_path("$").unwrap(), vec![]);
+        assert_eq!(
+            Key::parse_path("[1][2]").unwrap(),
+            vec![Key::Index(1), 2.into()]
+        );
+        assert_eq!(
+            Key::parse_path(r#"["\ud83d\ude00\u00e9"]"#).unwrap(),
+            vec![Key::map_key("😀é")]
+        );
+    }
+
+    #[test]
+    fn parse_path_errors() {
+        for (path, position) in [
+            ("a..b", 2),
+            ("a.", 2),
+            ("a[", 2),
+            ("a[x]", 2),
+            ("a[0", 3),
+            ("a[0]b", 4),
+            (r#"a["b"#, 4),
+            (r#"a["\q"]"#, 4),
+            (r#"a["\é"]"#, 4),
+            (r#"a["\"#, 3),
+            (r#"a["\ud83d"]"#, 9),
+            ("$a", 1),
+            ("a]", 1),
+            ("a.b]c", 3),
+            (r#"a.b"c""#, 3),
+            (r#"a"[0]"#, 1),
+        ] {
+            let err = Key::parse_path(path).unwrap_err();
+            assert_eq!(err.position(), position, "{path}: {err}");
+            // the position is always on a character boundary
+            let _ = &path[..err.position()];
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        let mut accum = Accumulator::new();
+        accum.add_failure_at("a", "first");
+        accum.add_warning_at(0, "second");
+        let err = crate::Result::from(accum).unwrap_err();
+        let summary: Vec<_> = err
+            .iter()
+            .map(|f| (f.keys().to_vec(), f.message(), f.severity()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (vec![Key::from("a")], "first", Severity::Error),
+                (vec![Key::from(0)], "second", Severity::Warning),
+            ]
+        );
+        assert_eq!(err.failures().len(), 2);
+        let failures = err.into_failures();
+        assert_eq!(failures[1].message(), "second");
+    }
+
+    #[test]
+    fn dynamic_field() {
+        let plugin_fields = vec!["colour".to_string(), "size".to_string()];
+        let mut accum = Accumulator::new();
+        for name in &plugin_fields {
+            accum.add_failure_at(name.clone(), "unsupported by plugin");
+        }
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.colour: unsupported by plugin
+   $.size: unsupported by plugin"
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut stage1 = Accumulator::new();
+        stage1.add_failure_at("x", "bad x");
+        let mut stage2 = Accumulator::new();
+        stage2.add_warning_at(0, "iffy");
+
+        let mut accum = Accumulator::new();
+        accum.with_key("outer", |a| {
+            a.merge(stage1);
+            a.merge_at("list", stage2);
+        });
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.outer.x: bad x
+   $.outer.list[0] (warning): iffy"
+        );
+    }
+
+    #[test]
+    fn merge_respects_limit() {
+        let mut other = Accumulator::new();
+        for _ in 0..5 {
+            other.add_failure("bad");
+        }
+        let mut accum = Accumulator::with_limit(2);
+        accum.merge(other);
+        assert_eq!(accum.len(), 2);
+    }
+
+    #[test]
+    #[should_panic = "accumulator limit must be at least 1"]
+    fn zero_limit() {
+        Accumulator::with_limit(0);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut accum = Accumulator::new();
+        for _ in 0..3 {
+            accum.add_failure_at("a", "bad");
+            accum.add_warning_at("a", "bad");
+            accum.add_failure_at("b", "bad");
+        }
+        let mut err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(err.len(), 9);
+        err.dedup();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.a: bad
+   $.a (warning): bad
+   $.b: bad"
+        );
+    }
+
+    #[test]
+    fn fail_on_warnings() {
+        let mut accum = Accumulator::new();
+        accum.add_warning("odd");
+        assert!(accum.take_result().is_ok());
+
+        accum.set_fail_on_warnings(true);
+        accum.add_warning("odd");
+        let (errors, warnings) = accum.take_result().unwrap_err().partition_severity();
+        assert!(errors.is_empty());
+        assert_eq!(warnings.len(), 1);
+
+        // the setting survives the reset
+        accum.add_warning("odd");
+        assert!(accum.take_result().is_err());
+    }
+
+    #[test]
+    fn diff() {
+        let mut before = Accumulator::new();
+        before.add_failure_at("a", "bad 1");
+        before.add_failure_at("a", "bad 2");
+        before.add_failure_coded_at("b", "x", "bad");
+        let before = crate::Result::from(before).unwrap_err();
+
+        let mut after = Accumulator::new();
+        after.add_failure_at("a", "bad 3");
+        after.add_failure_at("b", "bad");
+        let after = crate::Result::from(after).unwrap_err();
+
+        let diff = before.diff(&after);
+        let messages = |fs: &[&Failure]| {
+            fs.iter()
+                .map(|f| f.message().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(messages(&diff.persisted), ["bad 3"]);
+        assert_eq!(messages(&diff.appeared), ["bad"]);
+        assert_eq!(messages(&diff.disappeared), ["bad 2", "bad"]);
+        assert!(!diff.is_unchanged());
+        assert!(after.diff(&after).is_unchanged());
+    }
+
+    #[test]
+    fn iterator_traits() {
+        let mut err: Error = ["a", "b"].into_iter().map(Failure::from).collect();
+        err.extend(Some(Failure::new(&["c".into()], "c")));
+        let messages: Vec<_> = (&err).into_iter().map(|f| f.message()).collect();
+        assert_eq!(messages, ["a", "b", "c"]);
+        assert_eq!(err.into_iter().count(), 3);
+    }
+
+    #[test]
+    fn paths_shared() {
+        let mut accum = Accumulator::new();
+        accum.with_keys(&crate::path!["a", 0, "b", 1, "c"], |a| {
+            a.add_failure("short")
+        });
+        accum.with_keys(&crate::path!["a", 0, "b", 1, "c", 2, "d"], |a| {
+            a.add_failure("long");
+            a.add_warning("long");
+        });
+        accum.add_failure_at("e", "short");
+        let err = Result::from(accum).unwrap_err();
+        let failures = err.failures();
+        assert!(matches!(failures[0].key, Path::Inline(_)));
+        let (Path::Shared(first), Path::Shared(second)) = (&failures[1].key, &failures[2].key)
+        else {
+            panic!("long paths should be shared");
+        };
+        assert!(Arc::ptr_eq(first, second));
+        assert_eq!(failures[2].keys().len(), 7);
+        assert_eq!(failures[3].keys(), crate::path!["e"]);
+    }
+
+    #[cfg(feature = "stacker")]
+    #[test]
+    fn deep_nesting_grows_stack() {
+        /// A chain of the given length, without allocating (or recursively dropping) one.
+        struct Chain(usize);
+
+        impl Validate for Chain {
+            fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+                match self.0 {
+                    0 => accum.add_failure("end of chain"),
+                    n => accum.validate_member_at("next", &Chain(n - 1)),
+                }
+            }
+        }
+
+        let err = std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| Chain(100_000).validate().unwrap_err())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(err.failures()[0].keys().len(), 100_000);
+    }
+
+    #[test]
+    fn interning() {
+        fn shared(key: &Key) -> &Arc<str> {
+            match key {
+                Key::Field(KeyStr(KeyStrRepr::Shared(s)))
+                | Key::MapKey(KeyStr(KeyStrRepr::Shared(s))) => s,
+                _ => panic!("key should be shared"),
+            }
+        }
+
+        struct Bad;
+
+        impl Validate for Bad {
+            fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+                accum.add_failure("bad");
+            }
+        }
+
+        let map: BTreeMap<_, _> = [(1, Bad)].into_iter().collect();
+        let mut accum = Accumulator::new();
+        accum.set_interning(true);
+        for _ in 0..2 {
+            accum.add_failure_at(String::from("col"), "field");
+            accum.validate_map(&map);
+        }
+        let name = accum.intern("col");
+        let mut fork = accum.fork();
+        fork.add_failure_at(String::from("col"), "forked");
+        let err = Result::from(accum).unwrap_err();
+        let keys: Vec<_> = err.failures().iter().map(|f| &f.keys()[0]).collect();
+        assert!(Arc::ptr_eq(shared(keys[0]), shared(keys[2])));
+        assert!(Arc::ptr_eq(shared(keys[1]), shared(keys[3])));
+        assert!(Arc::ptr_eq(shared(keys[0]), shared(&Key::Field(name))));
+        assert_eq!(keys[1].to_string(), "[\"1\"]");
+        assert!(fork.interned.is_some());
+        let mut accum = Accumulator::new();
+        accum.set_interning(true);
+        let name = accum.intern("col");
+        accum.merge(fork);
+        let err = Result::from(accum).unwrap_err();
+        assert!(Arc::ptr_eq(
+            shared(&err.failures()[0].keys()[0]),
+            shared(&Key::Field(name))
+        ));
+
+        let mut accum = Accumulator::new();
+        accum.add_failure_at(String::from("col"), "field");
+        accum.add_failure_at(String::from("col"), "field");
+        let err = Result::from(accum).unwrap_err();
+        let keys: Vec<_> = err.failures().iter().map(|f| &f.keys()[0]).collect();
+        assert!(!Arc::ptr_eq(shared(keys[0]), shared(keys[1])));
+    }
+
+    #[test]
+    fn static_messages_borrowed() {
+        let mut accum = Accumulator::new();
+        accum.add_failure("literal");
+        accum.add_failure(format!("formatted {}", 1));
+        let err = Result::from(accum).unwrap_err();
+        assert!(matches!(
+            err.failures()[0].message,
+            Cow::Borrowed("literal")
+        ));
+        assert!(matches!(err.failures()[1].message, Cow::Owned(_)));
+    }
+}
@@ -0,0 +1,433 @@
+//! Implementations of [Validate] for standard library types.
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::BTreeMap,
+    rc::{self, Rc},
+    sync::{self, Arc},
+    vec::Vec,
+};
+use core::{
+    cell::RefCell,
+    fmt::Display,
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+};
+
+use crate::{Accumulator, FailureSink, Validate};
+
+/// `None` is valid.
+impl<T: Validate> Validate for Option<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        if let Some(v) = self {
+            v.validate_inner(accum)
+        }
+    }
+}
+
+impl<T: Validate> Validate for [T] {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_iter(self);
+    }
+}
+
+impl<T: Validate, const N: usize> Validate for [T; N] {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_iter(self);
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_iter(self);
+    }
+}
+
+/// Failures are ordered by the map's iteration order, which is arbitrary.
+#[cfg(feature = "std")]
+impl<K: Display, V: Validate, H> Validate for HashMap<K, V, H> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_map(self);
+    }
+}
+
+impl<K: Display, V: Validate> Validate for BTreeMap<K, V> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_map(self);
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K: Display, V: Validate, H> Validate for indexmap::IndexMap<K, V, H> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_map(self);
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Validate for smallvec::SmallVec<A>
+where
+    A::Item: Validate,
+{
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.validate_iter(self);
+    }
+}
+
+/// Also covers slices, as `&[T]`.
+impl<T: Validate + ?Sized> Validate for &T {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        (**self).validate_inner(accum)
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for &mut T {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        (**self).validate_inner(accum)
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        (**self).validate_inner(accum)
+    }
+}
+
+/// Supports cycle detection; see [Accumulator::set_cycle_detection].
+impl<T: Validate + ?Sized> Validate for Rc<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.guard_cycle(Rc::as_ptr(self), |a| (**self).validate_inner(a))
+    }
+}
+
+/// Supports cycle detection; see [Accumulator::set_cycle_detection].
+impl<T: Validate + ?Sized> Validate for Arc<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        accum.guard_cycle(Arc::as_ptr(self), |a| (**self).validate_inner(a))
+    }
+}
+
+/// A dropped value is valid.
+impl<T: Validate + ?Sized> Validate for rc::Weak<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        if let Some(rc) = self.upgrade() {
+            rc.validate_inner(accum)
+        }
+    }
+}
+
+/// A dropped value is valid.
+impl<T: Validate + ?Sized> Validate for sync::Weak<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        if let Some(arc) = self.upgrade() {
+            arc.validate_inner(accum)
+        }
+    }
+}
+
+impl<B: Validate + ToOwned + ?Sized> Validate for Cow<'_, B> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        (**self).validate_inner(accum)
+    }
+}
+
+/// Blocks until the lock is acquired, so must not be called while the current thread holds it.
+/// A poisoned lock is reported as a failure.
+#[cfg(feature = "std")]
+impl<T: Validate + ?Sized> Validate for Mutex<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        match self.lock() {
+            Ok(guard) => guard.validate_inner(accum),
+            Err(_) => accum.add_failure("lock is poisoned"),
+        }
+    }
+}
+
+/// Blocks until a read lock is acquired, so must not be called while the current thread holds a write lock.
+/// A poisoned lock is reported as a failure.
+#[cfg(feature = "std")]
+impl<T: Validate + ?Sized> Validate for RwLock<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        match self.read() {
+            Ok(guard) => guard.validate_inner(accum),
+            Err(_) => accum.add_failure("lock is poisoned"),
+        }
+    }
+}
+
+/// A value which is currently mutably borrowed is reported as a failure.
+impl<T: Validate + ?Sized> Validate for RefCell<T> {
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+        match self.try_borrow() {
+            Ok(r) => r.validate_inner(accum),
+            Err(_) => accum.add_failure("value is already mutably borrowed"),
+        }
+    }
+}
+
+macro_rules! impl_always_valid {
+    ($($t:ty),+) => {
+        $(
+            /// Always valid; enforced by construction.
+            impl Validate for $t {
+                fn validate_inner<S: FailureSink>(&self, _accum: &mut Accumulator<S>) {}
+            }
+        )+
+    };
+}
+
+impl_always_valid!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize
+);
+
+macro_rules! impl_tuple {
+    ($($idx:tt $t:ident),+) => {
+        impl<$($t: Validate),+> Validate for ($($t,)+) {
+            fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+                $(accum.validate_member_at($idx, &self.$idx);)+
+            }
+        }
+    };
+}
+
+impl_tuple!(0 T0);
+impl_tuple!(0 T0, 1 T1);
+impl_tuple!(0 T0, 1 T1, 2 T2);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7, 8 T8);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7, 8 T8, 9 T9);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7, 8 T8, 9 T9, 10 T10);
+impl_tuple!(0 T0, 1 T1, 2 T2, 3 T3, 4 T4, 5 T5, 6 T6, 7 T7, 8 T8, 9 T9, 10 T10, 11 T11);
+
+#[cfg(test)]
+mod tests {
+    use crate::{Accumulator, FailureSink, Validate};
+
+    #[derive(Clone)]
+    struct Even(u8);
+
+    impl Validate for Even {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if self.0 % 2 != 0 {
+                accum.add_failure("value is odd");
+            }
+        }
+    }
+
+    #[test]
+    fn sequences() {
+        let v = vec![Even(0), Even(1), Even(2), Even(3)];
+        let err = v.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1]: value is odd\n   $[3]: value is odd"
+        );
+        assert_eq!(v.as_slice().validate().unwrap_err().len(), 2);
+        assert_eq!([Even(1)].validate().unwrap_err().len(), 1);
+        assert!(Vec::<Even>::new().validate().is_ok());
+    }
+
+    #[test]
+    fn maps() {
+        let m: std::collections::BTreeMap<_, _> =
+            [("a", Even(1)), ("b", Even(2)), ("c\"d", Even(3))]
+                .into_iter()
+                .collect();
+        let err = m.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[\"a\"]: value is odd\n   $[\"c\\\"d\"]: value is odd"
+        );
+
+        #[cfg(feature = "std")]
+        {
+            let m: std::collections::HashMap<_, _> = [(1, vec![Even(1)])].into_iter().collect();
+            let err = m.validate().unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Validation failure(s):\n   $[\"1\"][0]: value is odd"
+            );
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn indexmap() {
+        let m: indexmap::IndexMap<_, _, std::collections::hash_map::RandomState> =
+            [("b", Even(1)), ("a", Even(3))].into_iter().collect();
+        let err = m.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[\"b\"]: value is odd\n   $[\"a\"]: value is odd"
+        );
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn smallvec() {
+        let v: smallvec::SmallVec<[Even; 2]> = smallvec::smallvec![Even(0), Even(1), Even(3)];
+        let err = v.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1]: value is odd\n   $[2]: value is odd"
+        );
+    }
+
+    #[test]
+    fn option() {
+        assert!(None::<Even>.validate().is_ok());
+        assert!(Some(Even(1)).validate().is_err());
+    }
+
+    #[test]
+    fn pointers() {
+        use std::{borrow::Cow, rc::Rc, sync::Arc};
+
+        assert!(Box::new(Even(1)).validate().is_err());
+        assert!(Rc::new(Even(1)).validate().is_err());
+        assert!(Arc::new(Even(1)).validate().is_err());
+
+        let boxed: Box<[Even]> = vec![Even(0), Even(1)].into_boxed_slice();
+        let err = boxed.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1]: value is odd"
+        );
+
+        let evens = [Even(0), Even(1)];
+        let cow: Cow<[Even]> = Cow::Borrowed(&evens);
+        assert_eq!(cow.validate().unwrap_err().len(), 1);
+    }
+
+    #[test]
+    fn references() {
+        fn check<V: Validate>(v: V) -> usize {
+            v.validate().map_or_else(|e| e.len(), |_| 0)
+        }
+
+        let mut odd = Even(1);
+        assert_eq!(check(&odd), 1);
+        assert_eq!(check(&mut odd), 1);
+        assert_eq!(check([&Even(1), &Even(3)]), 2);
+        assert_eq!(check(&[Even(1), Even(3)][..]), 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn locks() {
+        use std::{
+            cell::RefCell,
+            sync::{Arc, Mutex, RwLock},
+        };
+
+        assert!(Mutex::new(Even(0)).validate().is_ok());
+        assert!(RwLock::new(Even(1)).validate().is_err());
+
+        let cell = RefCell::new(Even(0));
+        assert!(cell.validate().is_ok());
+        let _borrowed = cell.borrow_mut();
+        assert!(cell.validate().is_err());
+
+        let poisoned = Arc::new(Mutex::new(Even(0)));
+        let p2 = Arc::clone(&poisoned);
+        let _ = std::thread::spawn(move || {
+            let _guard = p2.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join();
+        let err = poisoned.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $: lock is poisoned"
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        use std::{cell::RefCell, rc::Rc};
+
+        struct Node {
+            value: Even,
+            next: RefCell<Option<Rc<Node>>>,
+        }
+
+        impl Validate for Node {
+            fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+                accum.validate_member_at("value", &self.value);
+                accum.validate_member_at("next", &self.next);
+            }
+        }
+
+        let a = Rc::new(Node {
+            value: Even(0),
+            next: RefCell::new(None),
+        });
+        let b = Rc::new(Node {
+            value: Even(1),
+            next: RefCell::new(Some(Rc::clone(&a))),
+        });
+        // shared but acyclic is fine
+        let pair = (Rc::clone(&b), Rc::clone(&b));
+        let mut accum = Accumulator::new();
+        accum.set_cycle_detection(true);
+        pair.validate_inner(&mut accum);
+        assert_eq!(accum.len(), 2);
+
+        *a.next.borrow_mut() = Some(Rc::clone(&b));
+        let mut accum = Accumulator::new();
+        accum.set_cycle_detection(true);
+        b.validate_inner(&mut accum);
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.value: value is odd
+   $.next.next: cycle detected"
+        );
+
+        // break the cycle so the nodes can be dropped
+        a.next.borrow_mut().take();
+    }
+
+    #[test]
+    fn tuples() {
+        assert!((Even(0), Even(2)).validate().is_ok());
+        let err = (Even(0), vec![Even(1)], Even(3)).validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1][0]: value is odd\n   $[2]: value is odd"
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let v = vec![vec![Even(0)], vec![Even(2), Even(3)]];
+        let err = v.validate().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1][1]: value is odd"
+        );
+    }
+}
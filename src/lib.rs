@@ -1,9 +1,51 @@
 #![doc=include_str!("../README.md")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
+
 mod errors;
-pub use errors::{Accumulator, Error, Failure, Key, Result};
+mod impls;
+mod macros;
+pub use errors::{
+    Accumulator, BoxedError, Checkpoint, Error, ErrorDiff, Failure, Key, KeyStr, ParsePathError,
+    Result, Severity,
+};
+#[doc(hidden)]
+pub use macros::{__alloc, __validate_with};
 pub mod synch;
-pub use synch::{Validate, ValidateContext};
+pub use synch::{Validate, ValidateContext, ValidateProfile};
 mod wrapper;
-pub use wrapper::Valid;
+#[cfg(feature = "serde")]
+pub use wrapper::ValidSeed;
+pub use wrapper::{Valid, ValidMut, ValidRef};
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod rename;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub use rename::RenameRule;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "std")]
+mod pool;
+#[cfg(feature = "std")]
+pub use pool::{AccumulatorPool, PooledAccumulator};
+#[cfg(feature = "std")]
+mod stamped;
+#[cfg(feature = "std")]
+pub use stamped::{RevalidationPolicy, Stamped};
 
+#[cfg(feature = "arena")]
+pub mod arena;
 pub mod asynch;
+pub mod borrowed;
+pub mod constraints;
+pub mod localize;
+pub mod render;
+pub mod report;
+pub mod sink;
+pub mod span;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+#[cfg(not(feature = "metrics"))]
+mod telemetry;
+pub use sink::FailureSink;
@@ -0,0 +1,114 @@
+//! Localizing failure messages.
+//!
+//! Failures added with [Accumulator::add_failure_localized](crate::Accumulator::add_failure_localized)
+//! carry a [code](Failure::code) identifying the message and [parameters](Failure::params)
+//! to interpolate into it.
+//! [Error::localize](crate::Error::localize) replaces their messages using a [Translator].
+//!
+//! With the `fluent` feature, a [FluentBundle](fluent_bundle::bundle::FluentBundle)
+//! is a translator which uses the code as the message ID and the parameters as arguments.
+//! Parameters which look like numbers are passed as numbers, so they can be used for plural selection.
+//!
+//! ```
+//! # #[cfg(feature = "fluent")]
+//! # {
+//! use fluent_bundle::{FluentBundle, FluentResource};
+//! use validatrix::{Accumulator, Result};
+//!
+//! let ftl = "too-short = doit comporter au moins { $min } caractères";
+//! let mut bundle = FluentBundle::new(vec!["fr".parse().unwrap()]);
+//! bundle.set_use_isolating(false);
+//! bundle.add_resource(FluentResource::try_new(ftl.into()).unwrap()).unwrap();
+//!
+//! let mut accum = Accumulator::new();
+//! accum.with_key("name", |a| {
+//!     a.add_failure_localized("too-short", [("min", 3)], "must be at least 3 characters")
+//! });
+//! let mut err = Result::from(accum).unwrap_err();
+//! err.localize(&bundle);
+//! assert_eq!(
+//!     err.to_string(),
+//!     "Validation failure(s):\n   $.name: doit comporter au moins 3 caractères",
+//! );
+//! # }
+//! ```
+use crate::Failure;
+use alloc::string::String;
+
+/// Produces a localized message for a failure.
+pub trait Translator {
+    /// Return the localized message, or `None` to keep the failure's existing message.
+    fn translate(&self, failure: &Failure) -> Option<String>;
+}
+
+impl<F: Fn(&Failure) -> Option<String>> Translator for F {
+    fn translate(&self, failure: &Failure) -> Option<String> {
+        self(failure)
+    }
+}
+
+#[cfg(feature = "fluent")]
+impl<R, M> Translator for fluent_bundle::bundle::FluentBundle<R, M>
+where
+    R: core::borrow::Borrow<fluent_bundle::FluentResource>,
+    M: fluent_bundle::memoizer::MemoizerKind,
+{
+    /// Messages with formatting errors (e.g. missing arguments) are still used.
+    fn translate(&self, failure: &Failure) -> Option<String> {
+        let pattern = self.get_message(failure.code()?)?.value()?;
+        let mut args = fluent_bundle::FluentArgs::new();
+        for (name, value) in failure.params() {
+            args.set(name, fluent_bundle::FluentValue::try_number(value));
+        }
+        let mut errors = Vec::new();
+        Some(
+            self.format_pattern(pattern, Some(&args), &mut errors)
+                .into_owned(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Accumulator, Failure};
+
+    fn error() -> crate::Error {
+        let mut accum = Accumulator::new();
+        accum.add_failure_localized("too-many", [("count", 1)], "too many");
+        accum.add_failure_localized("too-many", [("count", 2)], "too many");
+        accum.add_failure_coded("unknown", "not translated");
+        accum.add_failure("plain");
+        crate::Result::from(accum).unwrap_err()
+    }
+
+    #[test]
+    fn closure() {
+        let mut err = error();
+        err.localize(&|f: &Failure| {
+            (f.code()? == "too-many").then(|| format!("trop: {}", f.param("count").unwrap()))
+        });
+        let messages: Vec<_> = err.iter().map(|f| f.message()).collect();
+        assert_eq!(messages, ["trop: 1", "trop: 2", "not translated", "plain"]);
+    }
+
+    #[cfg(feature = "fluent")]
+    #[test]
+    fn fluent() {
+        use fluent_bundle::{FluentBundle, FluentResource};
+
+        let ftl =
+            "too-many = { $count ->\n    [one] one too many\n   *[other] { $count } too many\n}";
+        let mut bundle = FluentBundle::new(vec!["en".parse().unwrap()]);
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(FluentResource::try_new(ftl.into()).unwrap())
+            .unwrap();
+        let mut err = error();
+        err.localize(&bundle);
+        let messages: Vec<_> = err.iter().map(|f| f.message()).collect();
+        assert_eq!(
+            messages,
+            ["one too many", "2 too many", "not translated", "plain"]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{Accumulator, FailureSink};
+
+/// Implement [Validate](crate::Validate) for newtypes by delegating to the wrapped value.
+///
+/// No key is added to the path, so the newtype is transparent in failure reports.
+/// By default, the wrapped value must itself implement `Validate`.
+/// Alternatively, give a constraint to apply to the wrapped value:
+/// any function or closure taking an [Accumulator] and a reference to the wrapped value.
+///
+/// ```
+/// use std::num::NonZeroU16;
+/// use validatrix::{constraints, impl_validate_transparent, Accumulator, Validate};
+///
+/// struct Port(NonZeroU16);
+/// struct Ports(Vec<Port>);
+/// impl_validate_transparent!(Port, Ports);
+///
+/// struct CardNumber(String);
+/// impl_validate_transparent!(CardNumber => |accum, value| constraints::luhn(accum, value));
+///
+/// struct Even(u8);
+/// impl_validate_transparent!(Even => |accum, value| if value % 2 != 0 {
+///     accum.add_failure("value is odd")
+/// });
+///
+/// assert!(CardNumber("79927398713".into()).validate().is_ok());
+/// assert!(Even(3).validate().is_err());
+/// ```
+#[macro_export]
+macro_rules! impl_validate_transparent {
+    ($t:ty => $constraint:expr) => {
+        impl $crate::Validate for $t {
+            fn validate_inner<S: $crate::FailureSink>(&self, accum: &mut $crate::Accumulator<S>) {
+                $crate::__validate_with(accum, &self.0, $constraint)
+            }
+        }
+    };
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl $crate::Validate for $t {
+                fn validate_inner<S: $crate::FailureSink>(&self, accum: &mut $crate::Accumulator<S>) {
+                    $crate::Validate::validate_inner(&self.0, accum)
+                }
+            }
+        )+
+    };
+}
+
+/// Used by [impl_validate_transparent] so that constraint closures' argument types can be inferred.
+#[doc(hidden)]
+pub fn __validate_with<T: ?Sized, S: FailureSink>(
+    accum: &mut Accumulator<S>,
+    value: &T,
+    constraint: impl FnOnce(&mut Accumulator<S>, &T),
+) {
+    constraint(accum, value)
+}
+
+/// Used by macros so that they work in `no_std` crates without `extern crate alloc`.
+#[doc(hidden)]
+pub mod __alloc {
+    pub use alloc::{boxed::Box, vec, vec::Vec};
+}
+
+/// Implement `TryFrom<T> for Valid<T>` for the given types, with [Error](crate::Error) as the error type.
+///
+/// This cannot be implemented generically for all `T: Validate`,
+/// as it would conflict with the standard library's blanket implementation of [TryFrom].
+///
+/// ```
+/// use validatrix::{impl_try_from_valid, Accumulator, FailureSink, Valid, Validate};
+///
+/// struct Port(u16);
+///
+/// impl Validate for Port {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 < 1024 {
+///             accum.add_failure("must not be privileged");
+///         }
+///     }
+/// }
+///
+/// impl_try_from_valid!(Port);
+///
+/// fn parse(s: &str) -> Result<Valid<Port>, Box<dyn std::error::Error>> {
+///     Ok(Port(s.parse()?).try_into()?)
+/// }
+///
+/// assert!(parse("8080").is_ok());
+/// assert!(parse("80").is_err());
+/// ```
+#[macro_export]
+macro_rules! impl_try_from_valid {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ::core::convert::TryFrom<$t> for $crate::Valid<$t> {
+                type Error = $crate::Error;
+
+                fn try_from(value: $t) -> $crate::Result<Self> {
+                    $crate::Valid::try_new(value)
+                }
+            }
+        )+
+    };
+}
+
+/// Implement [DynValidate](crate::asynch::DynValidate) for types which implement
+/// [asynch::Validate](crate::asynch::Validate), by boxing their futures.
+///
+/// Fails to compile if the futures are not `Send`.
+///
+/// ```
+/// use validatrix::{asynch::{DynValidate, Validate}, impl_dyn_validate, Accumulator, FailureSink};
+///
+/// struct Username(String);
+///
+/// impl Validate for Username {
+///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0.is_empty() {
+///             accum.add_failure("must not be empty");
+///         }
+///     }
+/// }
+///
+/// impl_dyn_validate!(Username);
+///
+/// let boxed: Box<dyn DynValidate> = Box::new(Username("".into()));
+/// assert!(pollster::block_on(boxed.validate()).is_err());
+/// ```
+#[macro_export]
+macro_rules! impl_dyn_validate {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl $crate::asynch::DynValidate for $t {
+                fn validate_inner_boxed<'a>(
+                    &'a self,
+                    accum: &'a mut $crate::Accumulator,
+                ) -> $crate::asynch::BoxFuture<'a, ()> {
+                    $crate::__alloc::Box::pin($crate::asynch::Validate::validate_inner(self, accum))
+                }
+            }
+        )+
+    };
+}
+
+/// Asynchronously validate several members of a struct concurrently,
+/// then merge their failures into the accumulator at the given keys, in order.
+///
+/// Each member is validated in a [fork](crate::Accumulator::fork) of the accumulator,
+/// so must implement [asynch::Validate](crate::asynch::Validate).
+/// The members can be of different types;
+/// the whole expression must be `.await`ed inside an async function.
+///
+/// ```
+/// use validatrix::{asynch::Validate, join_members, Accumulator, FailureSink};
+///
+/// struct Username(String);
+///
+/// impl Validate for Username {
+///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0.is_empty() {
+///             accum.add_failure("must not be empty");
+///         }
+///     }
+/// }
+///
+/// struct Account {
+///     name: Username,
+///     nickname: Username,
+/// }
+///
+/// impl Validate for Account {
+///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         join_members!(accum; "name" => &self.name, "nickname" => &self.nickname).await;
+///     }
+/// }
+///
+/// let account = Account {
+///     name: Username("".into()),
+///     nickname: Username("".into()),
+/// };
+/// let err = pollster::block_on(account.validate()).unwrap_err();
+/// assert_eq!(err.len(), 2);
+/// ```
+#[macro_export]
+macro_rules! join_members {
+    ($accum:expr; $($key:expr => $member:expr),+ $(,)?) => {
+        async {
+            let accum: &mut $crate::Accumulator<_> = $accum;
+            let members = $crate::join_members!(@join accum; $($key => $member),+).await;
+            $crate::asynch::__merge_members(accum, members);
+        }
+    };
+    (@join $accum:ident; $key:expr => $member:expr) => {
+        async {
+            $crate::__alloc::vec![$crate::asynch::__validate_forked(&*$accum, $key, $member).await]
+        }
+    };
+    (@join $accum:ident; $key:expr => $member:expr, $($rest_key:expr => $rest_member:expr),+) => {
+        $crate::asynch::__join_front(
+            $crate::asynch::__validate_forked(&*$accum, $key, $member),
+            $crate::join_members!(@join $accum; $($rest_key => $rest_member),+),
+        )
+    };
+}
+
+/// Build a path of [Key](crate::Key)s from anything which can be converted into a key.
+///
+/// String literals become fields, integers become indices;
+/// use [Key::map_key](crate::Key::map_key) for map entries.
+///
+/// ```
+/// use validatrix::{path, Failure, Key};
+///
+/// assert_eq!(
+///     path!["b", "cs", 0, "cvalue"],
+///     vec![Key::from("b"), "cs".into(), 0.into(), "cvalue".into()],
+/// );
+///
+/// let failure = Failure::new(&path!["env", Key::map_key("PATH")], "not set");
+/// assert_eq!(failure.to_string(), r#"$.env["PATH"]: not set"#);
+/// assert!(path![].is_empty());
+/// ```
+#[macro_export]
+macro_rules! path {
+    ($($key:expr),* $(,)?) => {
+        <$crate::__alloc::Vec<$crate::Key>>::from([$($crate::Key::from($key)),*])
+    };
+}
@@ -0,0 +1,109 @@
+//! Parallel validation with [rayon].
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::{Accumulator, FailureSink, Validate};
+
+impl<S: FailureSink> Accumulator<S> {
+    /// Like [Accumulator::validate_iter], but validating the items in parallel on rayon's thread pool.
+    ///
+    /// Each item is validated into a [fork](Accumulator::fork) of this accumulator,
+    /// and their failures are added to this one in the order of the items,
+    /// so the result is the same as [Accumulator::validate_iter].
+    /// This accumulator's limit is applied when the failures are added,
+    /// so items are validated even if earlier ones reach the limit.
+    ///
+    /// ```
+    /// use validatrix::{Accumulator, FailureSink, Validate};
+    ///
+    /// struct Record(u64);
+    ///
+    /// impl Validate for Record {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0 % 1000 == 999 {
+    ///             accum.add_failure("checksum mismatch");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let records: Vec<_> = (0..10_000).map(Record).collect();
+    /// let mut accum = Accumulator::new();
+    /// accum.validate_iter_par(&records);
+    /// let err = validatrix::Result::from(accum).unwrap_err();
+    /// assert_eq!(err.len(), 10);
+    /// assert_eq!(err.failures()[0].keys(), [999.into()]);
+    /// ```
+    pub fn validate_iter_par<'a, V, I>(&mut self, items: I)
+    where
+        V: Validate + Sync + ?Sized + 'a,
+        I: IntoParallelIterator<Item = &'a V>,
+        I::Iter: IndexedParallelIterator,
+    {
+        if self.is_full() {
+            return;
+        }
+        let items = items.into_par_iter();
+        if self.at_max_depth() {
+            for idx in 0..items.len() {
+                if self.is_full() {
+                    return;
+                }
+                self.add_failure_at(idx, "maximum validation depth exceeded");
+            }
+            return;
+        }
+        let fork = self.forker();
+        let subs: Vec<_> = items
+            .enumerate()
+            .filter_map(|(idx, item)| {
+                let mut sub = fork();
+                item.validate_inner(&mut sub);
+                (!sub.is_empty()).then_some((idx, sub))
+            })
+            .collect();
+        for (idx, sub) in subs {
+            if self.is_full() {
+                return;
+            }
+            self.merge_at(idx, sub);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Even(u32);
+
+    impl Validate for Even {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if self.0 % 2 != 0 {
+                accum.add_failure("value is odd");
+            }
+        }
+    }
+
+    #[test]
+    fn matches_serial() {
+        let items: Vec<_> = (0..1000).map(Even).collect();
+        let mut serial = Accumulator::new();
+        serial.validate_iter(&items);
+        let mut parallel = Accumulator::new();
+        parallel.validate_iter_par(&items);
+        assert_eq!(
+            crate::Result::from(serial).unwrap_err().to_string(),
+            crate::Result::from(parallel).unwrap_err().to_string(),
+        );
+    }
+
+    #[test]
+    fn limit() {
+        let items: Vec<_> = (0..1000).map(Even).collect();
+        let mut accum = Accumulator::new();
+        accum.set_limit(Some(3));
+        accum.with_key("items", |a| a.validate_iter_par(&items));
+        let err = crate::Result::from(accum).unwrap_err();
+        let keys: Vec<_> = err.failures().iter().map(|f| f.json_path()).collect();
+        assert_eq!(keys, ["$.items[1]", "$.items[3]", "$.items[5]"]);
+    }
+}
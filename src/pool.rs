@@ -0,0 +1,198 @@
+//! Reuse of accumulators' allocations between validations.
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{Accumulator, Failure, Validate};
+
+/// A pool of failure buffers, for handing out accumulators which reuse allocations
+/// from previous validations.
+///
+/// Useful for services which validate every incoming request,
+/// where each request would otherwise grow a new accumulator from empty.
+/// The pool can be shared between threads.
+///
+/// ```
+/// use validatrix::{Accumulator, AccumulatorPool, FailureSink, Validate};
+///
+/// struct Even(u8);
+///
+/// impl Validate for Even {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 % 2 != 0 {
+///             accum.add_failure("value is odd");
+///         }
+///     }
+/// }
+///
+/// let pool = AccumulatorPool::new().with_init(|| Accumulator::with_limit(10));
+///
+/// // e.g. in a request handler
+/// assert!(pool.validate(&Even(3)).is_err());
+///
+/// let mut accum = pool.get();
+/// accum.validate_member_at("value", &Even(2));
+/// assert!(accum.take_result().is_ok());
+/// ```
+pub struct AccumulatorPool {
+    buffers: Mutex<Vec<Vec<Failure>>>,
+    max_idle: usize,
+    init: Box<dyn Fn() -> Accumulator + Send + Sync>,
+}
+
+impl AccumulatorPool {
+    /// Create a pool handing out accumulators created with [Accumulator::new],
+    /// keeping up to 64 idle buffers.
+    pub fn new() -> Self {
+        Self {
+            buffers: Mutex::default(),
+            max_idle: 64,
+            init: Box::new(Accumulator::new),
+        }
+    }
+
+    /// Create the accumulators which are handed out with the given function,
+    /// e.g. to set a limit.
+    ///
+    /// Configuration should be done here rather than on the accumulators handed out,
+    /// as only their failure buffers are returned to the pool.
+    pub fn with_init(mut self, init: impl Fn() -> Accumulator + Send + Sync + 'static) -> Self {
+        self.init = Box::new(init);
+        self
+    }
+
+    /// Keep at most this many idle buffers; any more are freed when returned.
+    pub fn with_max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Get an accumulator which returns its failure buffer to the pool when dropped.
+    pub fn get(&self) -> PooledAccumulator<'_> {
+        let mut accum = (self.init)();
+        let buffer = self
+            .buffers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        if let Some(mut buffer) = buffer {
+            if buffer.capacity() > accum.buffer_mut().capacity() {
+                std::mem::swap(accum.buffer_mut(), &mut buffer);
+            }
+        }
+        PooledAccumulator { accum, pool: self }
+    }
+
+    /// Validate the value with a pooled accumulator.
+    pub fn validate<T: Validate + ?Sized>(&self, value: &T) -> crate::Result {
+        let mut accum = self.get();
+        value.validate_inner(&mut accum);
+        accum.take_result()
+    }
+
+    /// Number of idle buffers in the pool.
+    pub fn idle(&self) -> usize {
+        self.buffers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    fn put(&self, mut buffer: Vec<Failure>) {
+        if buffer.capacity() == 0 {
+            return;
+        }
+        buffer.clear();
+        let mut buffers = self.buffers.lock().unwrap_or_else(PoisonError::into_inner);
+        if buffers.len() < self.max_idle {
+            buffers.push(buffer);
+        }
+    }
+}
+
+impl Default for AccumulatorPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for AccumulatorPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AccumulatorPool")
+            .field("idle", &self.idle())
+            .field("max_idle", &self.max_idle)
+            .finish()
+    }
+}
+
+/// An [Accumulator] from an [AccumulatorPool],
+/// which returns its failure buffer to the pool when dropped.
+///
+/// Use [Accumulator::take_result] to get the result while keeping the buffer.
+#[derive(Debug)]
+pub struct PooledAccumulator<'a> {
+    accum: Accumulator,
+    pool: &'a AccumulatorPool,
+}
+
+impl Deref for PooledAccumulator<'_> {
+    type Target = Accumulator;
+
+    fn deref(&self) -> &Accumulator {
+        &self.accum
+    }
+}
+
+impl DerefMut for PooledAccumulator<'_> {
+    fn deref_mut(&mut self) -> &mut Accumulator {
+        &mut self.accum
+    }
+}
+
+impl Drop for PooledAccumulator<'_> {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(self.accum.buffer_mut()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FailureSink;
+
+    struct Odd(usize);
+
+    impl Validate for Odd {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            for idx in 0..self.0 {
+                accum.add_failure_at(idx, "bad");
+            }
+        }
+    }
+
+    #[test]
+    fn reuses_buffers() {
+        let pool = AccumulatorPool::new().with_max_idle(1);
+        assert_eq!(pool.validate(&Odd(10)).unwrap_err().len(), 10);
+        assert_eq!(pool.idle(), 1);
+
+        let mut first = pool.get();
+        assert!(first.buffer_mut().capacity() >= 10);
+        assert_eq!(pool.idle(), 0);
+        let second = pool.get();
+        assert_eq!(second.len(), 0);
+
+        first.add_failure("bad");
+        assert!(first.take_result().is_err());
+        drop(first);
+        drop(second);
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[test]
+    fn init() {
+        let pool = AccumulatorPool::new().with_init(|| Accumulator::with_limit(2));
+        assert_eq!(pool.validate(&Odd(10)).unwrap_err().len(), 2);
+    }
+}
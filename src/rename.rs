@@ -0,0 +1,152 @@
+use alloc::{borrow::Cow, string::String};
+
+use crate::Key;
+
+/// Converts Rust field names into the names used when serialized,
+/// mirroring serde's `#[serde(rename_all = "...")]`.
+///
+/// Use this when building keys in manual [Validate](crate::Validate) implementations,
+/// so that failure paths match the serialized payload rather than the Rust source.
+/// Field names are expected to be `snake_case`, as with serde.
+///
+/// ```
+/// use validatrix::{Accumulator, FailureSink, RenameRule, Validate};
+///
+/// const RENAME: RenameRule = RenameRule::CamelCase;
+///
+/// // #[derive(serde::Deserialize)]
+/// // #[serde(rename_all = "camelCase")]
+/// struct Server {
+///     max_connections: u16,
+/// }
+///
+/// impl Validate for Server {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.max_connections == 0 {
+///             accum.add_failure_at(RENAME.key("max_connections"), "must be positive");
+///         }
+///     }
+/// }
+///
+/// let err = Server { max_connections: 0 }.validate().unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "Validation failure(s):\n   $.maxConnections: must be positive",
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RenameRule {
+    /// Leave field names unchanged.
+    #[default]
+    None,
+    /// `lowercase`
+    LowerCase,
+    /// `UPPERCASE`
+    UpperCase,
+    /// `PascalCase`
+    PascalCase,
+    /// `camelCase`
+    CamelCase,
+    /// `snake_case`
+    SnakeCase,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnakeCase,
+    /// `kebab-case`
+    KebabCase,
+    /// `SCREAMING-KEBAB-CASE`
+    ScreamingKebabCase,
+}
+
+impl RenameRule {
+    /// Parse the name of a rule as it appears in serde's `rename_all` attribute, e.g. `"camelCase"`.
+    pub fn from_serde_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "lowercase" => Self::LowerCase,
+            "UPPERCASE" => Self::UpperCase,
+            "PascalCase" => Self::PascalCase,
+            "camelCase" => Self::CamelCase,
+            "snake_case" => Self::SnakeCase,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnakeCase,
+            "kebab-case" => Self::KebabCase,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebabCase,
+            _ => return None,
+        })
+    }
+
+    /// Rename a Rust field name.
+    ///
+    /// Any raw identifier prefix (`r#`) is removed, as serde does.
+    pub fn apply<'a>(&self, field: &'a str) -> Cow<'a, str> {
+        let field = field.strip_prefix("r#").unwrap_or(field);
+        match self {
+            Self::None | Self::LowerCase | Self::SnakeCase => Cow::Borrowed(field),
+            Self::UpperCase | Self::ScreamingSnakeCase => Cow::Owned(field.to_ascii_uppercase()),
+            Self::PascalCase => Cow::Owned(pascal_case(field)),
+            Self::CamelCase => {
+                let mut s = pascal_case(field);
+                if let Some(first) = s.get_mut(..1) {
+                    first.make_ascii_lowercase();
+                }
+                Cow::Owned(s)
+            }
+            Self::KebabCase => Cow::Owned(field.replace('_', "-")),
+            Self::ScreamingKebabCase => Cow::Owned(field.to_ascii_uppercase().replace('_', "-")),
+        }
+    }
+
+    /// Create a [Key::Field] for the renamed field.
+    ///
+    /// Does not allocate if the name is unchanged.
+    pub fn key(&self, field: &'static str) -> Key {
+        Key::Field(self.apply(field).into())
+    }
+}
+
+fn pascal_case(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut capitalize = true;
+    for c in field.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            out.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules() {
+        for (name, expected) in [
+            ("lowercase", "max_conn_count"),
+            ("UPPERCASE", "MAX_CONN_COUNT"),
+            ("PascalCase", "MaxConnCount"),
+            ("camelCase", "maxConnCount"),
+            ("snake_case", "max_conn_count"),
+            ("SCREAMING_SNAKE_CASE", "MAX_CONN_COUNT"),
+            ("kebab-case", "max-conn-count"),
+            ("SCREAMING-KEBAB-CASE", "MAX-CONN-COUNT"),
+        ] {
+            let rule = RenameRule::from_serde_name(name).unwrap();
+            assert_eq!(rule.apply("max_conn_count"), expected, "{name}");
+        }
+        assert_eq!(RenameRule::CamelCase.apply("r#type"), "type");
+        assert!(RenameRule::from_serde_name("Title Case").is_none());
+    }
+
+    #[test]
+    fn borrowed_when_unchanged() {
+        static NAME: &str = "a_b";
+        let Key::Field(name) = RenameRule::SnakeCase.key(NAME) else {
+            panic!("expected field");
+        };
+        assert_eq!(name.as_str().as_ptr(), NAME.as_ptr());
+    }
+}
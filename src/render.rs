@@ -0,0 +1,559 @@
+//! Rendering failure paths in different syntaxes.
+//!
+//! [Error](crate::Error) and [Failure](crate::Failure) use [Dotted] paths in their
+//! [Display](std::fmt::Display) implementations;
+//! [Error::display_with](crate::Error::display_with) and [Failure::display_with](crate::Failure::display_with)
+//! use any other [PathRenderer].
+//!
+//! Closures taking the path and a writer can also be used as renderers,
+//! e.g. for an XPath-like syntax:
+//!
+//! ```
+//! use std::fmt::Write;
+//! use validatrix::{Error, Failure, Key};
+//!
+//! let xpath = |path: &[Key], f: &mut dyn Write| {
+//!     for k in path {
+//!         match k {
+//!             Key::Index(n) => write!(f, "[{}]", n + 1)?,
+//!             Key::Field(s) => write!(f, "/{s}")?,
+//!             Key::MapKey(s) => write!(f, "/entry[@key={s:?}]")?,
+//!         }
+//!     }
+//!     Ok(())
+//! };
+//!
+//! let failure = Failure::new(&["server".into(), "port".into(), 0.into()], "bad");
+//! assert_eq!(failure.display_with(xpath).to_string(), "/server/port[1]: bad");
+//! ```
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Write};
+
+use crate::{errors::INDENT, Error, Failure, Key, Severity};
+
+/// Writes the path to a failure.
+pub trait PathRenderer {
+    /// Write the given path.
+    fn render(&self, path: &[Key], f: &mut dyn Write) -> core::fmt::Result;
+}
+
+impl<F: Fn(&[Key], &mut dyn Write) -> core::fmt::Result> PathRenderer for F {
+    fn render(&self, path: &[Key], f: &mut dyn Write) -> core::fmt::Result {
+        self(path, f)
+    }
+}
+
+/// The default syntax, like `$.b.cs[0].cvalue` or `$.env["PATH"]`.
+///
+/// Field names are written as-is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Dotted;
+
+impl PathRenderer for Dotted {
+    fn render(&self, path: &[Key], f: &mut dyn Write) -> core::fmt::Result {
+        f.write_char('$')?;
+        for k in path {
+            write!(f, "{k}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Strict JSONPath ([RFC 9535](https://www.rfc-editor.org/rfc/rfc9535)) syntax,
+/// as used by [Failure::json_path].
+///
+/// Like [Dotted], but field names which are not valid identifiers are quoted,
+/// e.g. `$.headers["content-type"]`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JsonPath;
+
+impl PathRenderer for JsonPath {
+    fn render(&self, path: &[Key], f: &mut dyn Write) -> core::fmt::Result {
+        f.write_char('$')?;
+        for k in path {
+            k.write_json_path(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// JSON Pointer ([RFC 6901](https://www.rfc-editor.org/rfc/rfc6901)) syntax, like `/b/cs/0/cvalue`.
+///
+/// The root is the empty string;
+/// when displaying a failure at the root, the separator after the path is left out,
+/// like `(warning): message` or just `message`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JsonPointer;
+
+impl PathRenderer for JsonPointer {
+    fn render(&self, path: &[Key], f: &mut dyn Write) -> core::fmt::Result {
+        for k in path {
+            f.write_char('/')?;
+            match k {
+                Key::Index(n) => write!(f, "{n}")?,
+                Key::Field(s) => write_pointer_token(f, s)?,
+                Key::MapKey(s) => write_pointer_token(f, s)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_pointer_token(f: &mut dyn Write, s: &str) -> core::fmt::Result {
+    for c in s.chars() {
+        match c {
+            '~' => f.write_str("~0")?,
+            '/' => f.write_str("~1")?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Passes writes through, remembering whether anything was written.
+///
+/// Used to leave out the separator after a path which renders as nothing,
+/// like the root in [JsonPointer].
+pub(crate) struct Tracked<'a> {
+    inner: &'a mut dyn Write,
+    pub(crate) written: bool,
+}
+
+impl<'a> Tracked<'a> {
+    pub(crate) fn new(inner: &'a mut dyn Write) -> Self {
+        Self {
+            inner,
+            written: false,
+        }
+    }
+}
+
+impl Write for Tracked<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.written |= !s.is_empty();
+        self.inner.write_str(s)
+    }
+}
+
+/// Displays an [Error] or [Failure] using a particular [PathRenderer].
+///
+/// Created by [Error::display_with] and [Failure::display_with].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayWith<'a, T, R> {
+    value: &'a T,
+    renderer: R,
+}
+
+impl<'a, T, R> DisplayWith<'a, T, R> {
+    pub(crate) fn new(value: &'a T, renderer: R) -> Self {
+        Self { value, renderer }
+    }
+}
+
+impl<R: PathRenderer> Display for DisplayWith<'_, Error, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.value.fmt_with(&self.renderer, f)
+    }
+}
+
+impl<R: PathRenderer> Display for DisplayWith<'_, Failure, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.value.fmt_with(&self.renderer, f)
+    }
+}
+
+/// Options for displaying an [Error], used by [Error::display_opts].
+///
+/// ```
+/// use validatrix::{render::DisplayOptions, Accumulator, Result};
+///
+/// let mut accum = Accumulator::new();
+/// for key in ["a", "b", "c"] {
+///     accum.add_failure_at(key, "bad");
+/// }
+/// let err = Result::from(accum).unwrap_err();
+/// let opts = DisplayOptions {
+///     max_failures: Some(2),
+///     counts: true,
+///     ..DisplayOptions::one_line()
+/// };
+/// assert_eq!(
+///     err.display_opts(&opts).to_string(),
+///     "Validation failure(s) (3 errors, 0 warnings): $.a: bad; $.b: bad; ... and 1 more",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOptions {
+    /// Put before each failure in multi-line mode; default three spaces.
+    pub indent: Cow<'static, str>,
+    /// Show at most this many failures, followed by a count of those not shown.
+    pub max_failures: Option<usize>,
+    /// Separate failures with `; ` rather than putting each on its own line,
+    /// e.g. for log lines.
+    pub one_line: bool,
+    /// Include the number of errors and warnings in the header.
+    pub counts: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            indent: Cow::Borrowed(INDENT),
+            max_failures: None,
+            one_line: false,
+            counts: false,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Options for displaying the error on a single line.
+    pub fn one_line() -> Self {
+        Self {
+            one_line: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Displays an [Error] using [DisplayOptions].
+///
+/// Created by [Error::display_opts].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayOpts<'a> {
+    pub(crate) error: &'a Error,
+    pub(crate) options: &'a DisplayOptions,
+}
+
+impl Display for DisplayOpts<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let opts = self.options;
+        f.write_str("Validation failure(s)")?;
+        if opts.counts {
+            let errors = self.error.errors().count();
+            let warnings = self.error.len() - errors;
+            let plural = |n: usize| if n == 1 { "" } else { "s" };
+            write!(
+                f,
+                " ({errors} error{}, {warnings} warning{})",
+                plural(errors),
+                plural(warnings)
+            )?;
+        }
+        f.write_char(':')?;
+        let sep = |f: &mut core::fmt::Formatter<'_>, first: bool| {
+            if !opts.one_line {
+                f.write_char('\n')?;
+                f.write_str(&opts.indent)
+            } else if first {
+                f.write_char(' ')
+            } else {
+                f.write_str("; ")
+            }
+        };
+        let shown = opts.max_failures.unwrap_or(usize::MAX);
+        for (idx, failure) in self.error.iter().take(shown).enumerate() {
+            sep(f, idx == 0)?;
+            Display::fmt(failure, f)?;
+        }
+        let hidden = self.error.len().saturating_sub(shown);
+        if hidden > 0 {
+            sep(f, shown == 0)?;
+            write!(f, "... and {hidden} more")?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays an [Error] with ANSI colors for terminals.
+///
+/// Created by [Error::display_colored].
+/// Colors are omitted if the `NO_COLOR` environment variable is set and not empty,
+/// unless overridden with [Colored::with_color].
+#[cfg(feature = "color")]
+#[derive(Debug, Clone, Copy)]
+pub struct Colored<'a> {
+    pub(crate) error: &'a Error,
+    pub(crate) enabled: Option<bool>,
+}
+
+#[cfg(feature = "color")]
+impl Colored<'_> {
+    /// Whether to use colors, regardless of the environment.
+    pub fn with_color(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+            .unwrap_or_else(|| std::env::var_os("NO_COLOR").map_or(true, |v| v.is_empty()))
+    }
+}
+
+#[cfg(feature = "color")]
+impl Display for Colored<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use anstyle::{AnsiColor, Style};
+
+        if !self.is_enabled() {
+            return Display::fmt(self.error, f);
+        }
+        let header = Style::new().bold();
+        let path = AnsiColor::Cyan.on_default();
+        let error = AnsiColor::Red.on_default();
+        let warning = AnsiColor::Yellow.on_default();
+
+        write!(
+            f,
+            "{}Validation failure(s):{}",
+            header.render(),
+            header.render_reset()
+        )?;
+        for failure in self.error.iter() {
+            f.write_char('\n')?;
+            f.write_str(INDENT)?;
+            write!(f, "{}", path.render())?;
+            Dotted.render(&failure.key, f)?;
+            write!(f, "{}", path.render_reset())?;
+            let (style, label) = match failure.severity {
+                Severity::Error => (error, "error"),
+                Severity::Warning => (warning, "warning"),
+            };
+            let bold = style.bold();
+            write!(f, " {}{label}{}: ", bold.render(), bold.render_reset())?;
+            write!(
+                f,
+                "{}{}{}",
+                style.render(),
+                failure.message,
+                style.render_reset()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays an [Error] with failures grouped under their common path prefixes.
+///
+/// Created by [Error::display_tree].
+#[derive(Debug, Clone, Copy)]
+pub struct Tree<'a>(pub(crate) &'a Error);
+
+/// Failures and children at one point in the path, in the order they were first reported.
+#[derive(Default)]
+struct Node<'a> {
+    failures: Vec<&'a Failure>,
+    children: Vec<(&'a Key, Node<'a>)>,
+    index: BTreeMap<&'a Key, usize>,
+}
+
+impl<'a> Node<'a> {
+    fn insert(&mut self, keys: &'a [Key], failure: &'a Failure) {
+        let Some((first, rest)) = keys.split_first() else {
+            self.failures.push(failure);
+            return;
+        };
+        let idx = *self.index.entry(first).or_insert_with(|| {
+            self.children.push((first, Node::default()));
+            self.children.len() - 1
+        });
+        self.children[idx].1.insert(rest, failure);
+    }
+
+    fn write(&self, label: &mut String, depth: usize, f: &mut dyn Write) -> core::fmt::Result {
+        // collapse chains of keys which have nothing else at them
+        if self.failures.is_empty() && self.children.len() == 1 {
+            let (key, child) = &self.children[0];
+            write!(label, "{key}")?;
+            return child.write(label, depth, f);
+        }
+        let line = |f: &mut dyn Write| {
+            f.write_char('\n')?;
+            for _ in 0..depth {
+                f.write_str(INDENT)?;
+            }
+            f.write_str(label)
+        };
+        for failure in self.failures.iter() {
+            line(f)?;
+            if failure.severity == Severity::Warning {
+                f.write_str(" (warning)")?;
+            }
+            f.write_str(": ")?;
+            f.write_str(&failure.message)?;
+        }
+        if self.children.is_empty() {
+            return Ok(());
+        }
+        if self.failures.is_empty() {
+            line(f)?;
+        }
+        for (key, child) in self.children.iter() {
+            let mut label = key.to_string();
+            child.write(&mut label, depth + 1, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Tree<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut root = Node::default();
+        for failure in self.0.iter() {
+            root.insert(&failure.key, failure);
+        }
+        f.write_str("Validation failure(s):")?;
+        root.write(&mut String::from("$"), 1, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure() -> Failure {
+        Failure::new(
+            &[
+                "b".into(),
+                String::from("a/b~c").into(),
+                0.into(),
+                Key::map_key("content-type"),
+            ],
+            "bad",
+        )
+    }
+
+    #[test]
+    fn renderers() {
+        let f = failure();
+        assert_eq!(f.display_with(Dotted).to_string(), f.to_string());
+        assert_eq!(
+            f.display_with(JsonPath).to_string(),
+            r#"$.b["a/b~c"][0]["content-type"]: bad"#
+        );
+        assert_eq!(
+            f.display_with(JsonPointer).to_string(),
+            "/b/a~1b~0c/0/content-type: bad"
+        );
+        assert_eq!(
+            Failure::new(&[], "bad")
+                .display_with(JsonPointer)
+                .to_string(),
+            "bad"
+        );
+    }
+
+    #[test]
+    fn tree() {
+        let mut accum = crate::Accumulator::new();
+        accum.add_failure("root");
+        accum.with_key("servers", |a| {
+            a.with_key(3, |a| {
+                a.add_failure_at("host", "must not be empty");
+                a.with_key("port", |a| {
+                    a.add_failure("must be positive");
+                    a.add_warning("is privileged");
+                });
+            });
+            a.add_failure_at(0, "first");
+        });
+        accum.with_keys(&crate::path!["a", "b", "c"], |a| a.add_failure("deep"));
+        accum.with_keys(&crate::path!["servers", 3, "host"], |a| {
+            a.add_failure("later");
+        });
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.display_tree().to_string(),
+            "Validation failure(s):
+   $: root
+      .servers
+         [3]
+            .host: must not be empty
+            .host: later
+            .port: must be positive
+            .port (warning): is privileged
+         [0]: first
+      .a.b.c: deep"
+        );
+    }
+
+    #[test]
+    fn tree_collapsed() {
+        let err = Error::from(failure());
+        assert_eq!(
+            err.display_tree().to_string(),
+            "Validation failure(s):\n   $.b.a/b~c[0][\"content-type\"]: bad"
+        );
+    }
+
+    #[test]
+    fn options() {
+        let mut err = Error::from(failure());
+        err.0
+            .push(Failure::new(&[], "root").with_severity(crate::Severity::Warning));
+        let opts = DisplayOptions {
+            indent: "- ".into(),
+            counts: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            err.display_opts(&opts).to_string(),
+            "Validation failure(s) (1 error, 1 warning):\n- $.b.a/b~c[0][\"content-type\"]: bad\n- $ (warning): root"
+        );
+        let opts = DisplayOptions {
+            max_failures: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            err.display_opts(&opts).to_string(),
+            "Validation failure(s):\n   ... and 2 more"
+        );
+        let opts = DisplayOptions {
+            max_failures: Some(0),
+            ..DisplayOptions::one_line()
+        };
+        assert_eq!(
+            err.display_opts(&opts).to_string(),
+            "Validation failure(s): ... and 2 more"
+        );
+        assert_eq!(
+            err.display_opts(&DisplayOptions::default()).to_string(),
+            err.to_string()
+        );
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn colored() {
+        let mut err = Error::from(failure());
+        err.0
+            .push(Failure::new(&[], "root").with_severity(crate::Severity::Warning));
+        assert_eq!(
+            err.display_colored().with_color(false).to_string(),
+            err.to_string()
+        );
+        assert_eq!(
+            err.display_colored().with_color(true).to_string(),
+            "\x1b[1mValidation failure(s):\x1b[0m
+   \x1b[36m$.b.a/b~c[0][\"content-type\"]\x1b[0m \x1b[1m\x1b[31merror\x1b[0m: \x1b[31mbad\x1b[0m
+   \x1b[36m$\x1b[0m \x1b[1m\x1b[33mwarning\x1b[0m: \x1b[33mroot\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn error() {
+        let mut err = Error::from(failure());
+        err.0
+            .push(Failure::new(&[], "root").with_severity(crate::Severity::Warning));
+        assert_eq!(
+            err.display_with(JsonPointer).to_string(),
+            "Validation failure(s):\n   /b/a~1b~0c/0/content-type: bad\n   (warning): root"
+        );
+    }
+}
@@ -0,0 +1,494 @@
+//! Structured reports of validation errors in common response formats.
+use crate::{
+    render::{Dotted, JsonPointer, PathRenderer},
+    Error, Failure, Severity,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// An [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457) problem details object
+/// (`application/problem+json`), created by [Error::to_problem_details].
+///
+/// Each failure is listed in the `errors` extension member, with its location as a JSON Pointer.
+///
+/// With the `serde` feature, this serializes to the problem details JSON format.
+///
+/// ```
+/// # use validatrix::{Accumulator, Result};
+/// let mut accum = Accumulator::new();
+/// accum.add_failure_coded_at("age", "positive", "must be a positive integer");
+/// let err = Result::from(accum).unwrap_err();
+///
+/// let problem = err
+///     .to_problem_details(422, "https://example.com/probs/invalid")
+///     .with_instance("/account/12345");
+/// assert_eq!(problem.status, 422);
+/// assert_eq!(problem.errors[0].pointer, "#/age");
+/// # #[cfg(feature = "serde")]
+/// assert_eq!(
+///     serde_json::to_value(&problem).unwrap(),
+///     serde_json::json!({
+///         "type": "https://example.com/probs/invalid",
+///         "title": "Validation failed",
+///         "status": 422,
+///         "detail": "1 error(s)",
+///         "instance": "/account/12345",
+///         "errors": [{
+///             "detail": "must be a positive integer",
+///             "pointer": "#/age",
+///             "code": "positive",
+///             "severity": "error",
+///         }],
+///     }),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemDetails {
+    /// URI identifying the problem type, serialized as `type`.
+    pub type_uri: String,
+    /// Short summary of the problem type.
+    pub title: String,
+    /// HTTP status code.
+    pub status: u16,
+    /// Explanation of this occurrence of the problem.
+    pub detail: String,
+    /// URI identifying this occurrence of the problem; omitted if `None`.
+    pub instance: Option<String>,
+    /// The individual failures.
+    pub errors: Vec<ProblemError>,
+}
+
+impl ProblemDetails {
+    /// Set the URI identifying this occurrence of the problem.
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+}
+
+/// One failure within [ProblemDetails].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProblemError {
+    /// The failure's message.
+    pub detail: String,
+    /// Location of the failure in the request body,
+    /// as a JSON Pointer in URI fragment form (e.g. `#/items/0/name`),
+    /// with characters not allowed in a fragment percent-encoded (e.g. `#/content%20type`).
+    pub pointer: String,
+    /// The failure's [code](Failure::code); omitted if `None`.
+    pub code: Option<String>,
+    /// The failure's severity.
+    pub severity: Severity,
+}
+
+impl From<&Failure> for ProblemError {
+    fn from(failure: &Failure) -> Self {
+        let mut rendered = String::new();
+        // writing to a String cannot fail
+        let _ = JsonPointer.render(&failure.key, &mut rendered);
+        Self {
+            detail: failure.message.to_string(),
+            pointer: fragment(&rendered),
+            code: failure.code().map(Into::into),
+            severity: failure.severity,
+        }
+    }
+}
+
+/// Percent-encode a JSON Pointer as a URI fragment
+/// ([RFC 6901 §6](https://www.rfc-editor.org/rfc/rfc6901#section-6)).
+fn fragment(pointer: &str) -> String {
+    let mut out = String::with_capacity(pointer.len() + 1);
+    out.push('#');
+    for b in pointer.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => out.push(b as char),
+            // unreserved, sub-delims and the other characters allowed in a fragment
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' | b'/' | b'?' => out.push(b as char),
+            b => out.push_str(&format!("%{b:02X}")),
+        }
+    }
+    out
+}
+
+impl Error {
+    /// Convert into an [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457) problem details object.
+    ///
+    /// `type_uri` may be `"about:blank"` if there is no more specific problem type.
+    pub fn to_problem_details(&self, status: u16, type_uri: impl Into<String>) -> ProblemDetails {
+        let errors = self.errors().count();
+        let warnings = self.len() - errors;
+        let mut detail = format!("{errors} error(s)");
+        if warnings > 0 {
+            detail.push_str(&format!(" and {warnings} warning(s)"));
+        }
+        ProblemDetails {
+            type_uri: type_uri.into(),
+            title: "Validation failed".into(),
+            status,
+            detail,
+            instance: None,
+            errors: self.iter().map(ProblemError::from).collect(),
+        }
+    }
+}
+
+impl Error {
+    /// Render as a Markdown table of path, severity and message,
+    /// e.g. for CI job summaries.
+    ///
+    /// Paths are rendered with [Dotted].
+    /// Markdown syntax in the cells is escaped, and line breaks become `<br>`.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.add_failure_at("name", "must match a|b");
+    /// accum.add_warning_at("tags", "should not be *empty*");
+    /// assert_eq!(
+    ///     Result::from(accum).unwrap_err().to_markdown(),
+    ///     "\
+    /// | Path | Severity | Message |
+    /// | --- | --- | --- |
+    /// | $.name | error | must match a\\|b |
+    /// | $.tags | warning | should not be \\*empty\\* |
+    /// ",
+    /// );
+    /// ```
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("| Path | Severity | Message |\n| --- | --- | --- |\n");
+        for failure in self.iter() {
+            out.push_str("| ");
+            push_markdown(&mut out, &path(failure));
+            out.push_str(" | ");
+            out.push_str(severity(failure.severity));
+            out.push_str(" | ");
+            push_markdown(&mut out, failure.message());
+            out.push_str(" |\n");
+        }
+        out
+    }
+
+    /// Render as an HTML `<table>` of path, severity and message,
+    /// e.g. for admin pages.
+    ///
+    /// Paths are rendered with [Dotted], and all text is escaped.
+    /// Each row has a `class` of `error` or `warning` for styling.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.add_failure_at("name", "must not contain <script>");
+    /// let html = Result::from(accum).unwrap_err().to_html();
+    /// assert!(html.contains(
+    ///     "<tr class=\"error\"><td>$.name</td><td>error</td><td>must not contain &lt;script&gt;</td></tr>"
+    /// ));
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<table>\n<thead><tr><th>Path</th><th>Severity</th><th>Message</th></tr></thead>\n<tbody>\n",
+        );
+        for failure in self.iter() {
+            let severity = severity(failure.severity);
+            out.push_str("<tr class=\"");
+            out.push_str(severity);
+            out.push_str("\"><td>");
+            push_html(&mut out, &path(failure));
+            out.push_str("</td><td>");
+            out.push_str(severity);
+            out.push_str("</td><td>");
+            push_html(&mut out, failure.message());
+            out.push_str("</td></tr>\n");
+        }
+        out.push_str("</tbody>\n</table>\n");
+        out
+    }
+}
+
+fn path(failure: &Failure) -> String {
+    let mut path = String::new();
+    // writing to a String cannot fail
+    let _ = Dotted.render(&failure.key, &mut path);
+    path
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+fn push_markdown(out: &mut String, text: &str) {
+    let mut lines = text.lines();
+    if let Some(line) = lines.next() {
+        push_markdown_line(out, line);
+    }
+    for line in lines {
+        out.push_str("<br>");
+        push_markdown_line(out, line);
+    }
+}
+
+fn push_markdown_line(out: &mut String, line: &str) {
+    for c in line.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '\\' | '`' | '*' | '_' | '[' | ']' | '|' | '~' | '#' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+}
+
+fn push_html(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ProblemDetails {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("type", &self.type_uri)?;
+        map.serialize_entry("title", &self.title)?;
+        map.serialize_entry("status", &self.status)?;
+        map.serialize_entry("detail", &self.detail)?;
+        if let Some(instance) = &self.instance {
+            map.serialize_entry("instance", instance)?;
+        }
+        map.serialize_entry("errors", &self.errors)?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ProblemError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("detail", &self.detail)?;
+        map.serialize_entry("pointer", &self.pointer)?;
+        if let Some(code) = &self.code {
+            map.serialize_entry("code", code)?;
+        }
+        map.serialize_entry("severity", &self.severity)?;
+        map.end()
+    }
+}
+
+/// A GraphQL error in the conventional shape for rejected input, created by [Error::to_graphql_error].
+///
+/// Serializes like:
+///
+/// ```json
+/// {
+///   "message": "Validation failed",
+///   "extensions": {
+///     "code": "BAD_USER_INPUT",
+///     "validation": [
+///       {"path": ["input", "items", 0, "name"], "message": "must not be empty", "code": "required"}
+///     ]
+///   }
+/// }
+/// ```
+///
+/// Map keys are included in paths as strings; `code` is omitted from validation entries without one.
+/// Warnings are not included.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct GraphQlError<'a> {
+    /// Top-level error message.
+    pub message: String,
+    /// Error code in the extensions; `"BAD_USER_INPUT"` by default.
+    pub code: String,
+    /// Failures with [Severity::Error].
+    pub failures: Vec<&'a Failure>,
+}
+
+#[cfg(feature = "serde")]
+impl Error {
+    /// Convert into a GraphQL error, for resolvers rejecting invalid input.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, Result};
+    /// let mut accum = Accumulator::new();
+    /// accum.with_key("input", |a| a.add_failure_coded_at("name", "required", "must not be empty"));
+    /// let err = Result::from(accum).unwrap_err();
+    /// assert_eq!(
+    ///     serde_json::to_value(err.to_graphql_error()).unwrap(),
+    ///     serde_json::json!({
+    ///         "message": "Validation failed",
+    ///         "extensions": {
+    ///             "code": "BAD_USER_INPUT",
+    ///             "validation": [
+    ///                 {"path": ["input", "name"], "message": "must not be empty", "code": "required"},
+    ///             ],
+    ///         },
+    ///     }),
+    /// );
+    /// ```
+    pub fn to_graphql_error(&self) -> GraphQlError<'_> {
+        GraphQlError {
+            message: "Validation failed".into(),
+            code: "BAD_USER_INPUT".into(),
+            failures: self.errors().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for GraphQlError<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        struct Extensions<'a>(&'a GraphQlError<'a>);
+        struct Entry<'a>(&'a Failure);
+        struct Path<'a>(&'a [crate::Key]);
+
+        impl serde::Serialize for Extensions<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("code", &self.0.code)?;
+                let entries: Vec<_> = self.0.failures.iter().map(|f| Entry(f)).collect();
+                map.serialize_entry("validation", &entries)?;
+                map.end()
+            }
+        }
+
+        impl serde::Serialize for Entry<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("path", &Path(&self.0.key))?;
+                map.serialize_entry("message", &self.0.message)?;
+                if let Some(code) = self.0.code() {
+                    map.serialize_entry("code", code)?;
+                }
+                map.end()
+            }
+        }
+
+        impl serde::Serialize for Path<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                use crate::Key;
+                use serde::ser::SerializeSeq;
+
+                let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+                for key in self.0 {
+                    match key {
+                        Key::Index(n) => seq.serialize_element(n)?,
+                        Key::Field(s) | Key::MapKey(s) => seq.serialize_element(s.as_str())?,
+                    }
+                }
+                seq.end()
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("message", &self.message)?;
+        map.serialize_entry("extensions", &Extensions(self))?;
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{path, Accumulator, Key, Severity};
+
+    #[test]
+    fn problem_details() {
+        let mut accum = Accumulator::new();
+        accum.with_keys(&path!["items", 0, Key::map_key("a/b")], |a| {
+            a.add_failure("bad")
+        });
+        accum.add_warning("odd");
+        let err = crate::Result::from(accum).unwrap_err();
+        let problem = err.to_problem_details(400, "about:blank");
+        assert_eq!(problem.detail, "1 error(s) and 1 warning(s)");
+        assert_eq!(problem.instance, None);
+        assert_eq!(problem.errors[0].pointer, "#/items/0/a~1b");
+        assert_eq!(problem.errors[0].code, None);
+        assert_eq!(problem.errors[1].pointer, "#");
+        assert_eq!(problem.errors[1].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn pointer_encoding() {
+        let mut accum = Accumulator::new();
+        accum.add_failure_at(Key::map_key("a b%c\"é#"), "bad");
+        let err = crate::Result::from(accum).unwrap_err();
+        let problem = err.to_problem_details(400, "about:blank");
+        assert_eq!(problem.errors[0].pointer, "#/a%20b%25c%22%C3%A9%23");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn graphql() {
+        let mut accum = Accumulator::new();
+        accum.with_keys(&path!["items", 0, Key::map_key("a")], |a| {
+            a.add_failure("bad")
+        });
+        accum.add_warning("odd");
+        let err = crate::Result::from(accum).unwrap_err();
+        let mut gql = err.to_graphql_error();
+        gql.code = "INVALID".into();
+        assert_eq!(
+            serde_json::to_value(gql).unwrap(),
+            serde_json::json!({
+                "message": "Validation failed",
+                "extensions": {
+                    "code": "INVALID",
+                    "validation": [{"path": ["items", 0, "a"], "message": "bad"}],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let mut accum = Accumulator::new();
+        accum.add_failure_at(Key::map_key("a|b"), "line 1\nline `2` <br> & [x](y)");
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_markdown().lines().nth(2).unwrap(),
+            r#"| $\["a\|b"\] | error | line 1<br>line \`2\` &lt;br&gt; &amp; \[x\](y) |"#
+        );
+    }
+
+    #[test]
+    fn html() {
+        let mut accum = Accumulator::new();
+        accum.set_fail_on_warnings(true);
+        accum.add_warning_at(Key::map_key("it's"), "a & b");
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_html(),
+            "<table>
+<thead><tr><th>Path</th><th>Severity</th><th>Message</th></tr></thead>
+<tbody>
+<tr class=\"warning\"><td>$[&quot;it&#39;s&quot;]</td><td>warning</td><td>a &amp; b</td></tr>
+</tbody>
+</table>
+"
+        );
+    }
+}
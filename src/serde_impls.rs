@@ -0,0 +1,514 @@
+//! Serialization of validation errors.
+//!
+//! Manually implemented to save depending on the derive feature of serde.
+//!
+//! - [Error] is a sequence of [Failure]s
+//! - [Failure] is a struct with fields
+//!   `path` (the [JSONPath](Failure::json_path) of the failure, ignored when deserializing),
+//!   `keys`, `message`, `severity`, `code`, `params` (a map of strings), `source` (the message of the source error, if any)
+//!   and `span` (an object with `offset`, `line` and `column`, if known).
+//!   Payloads are not serialized.
+//! - [Key::Index] is an integer, [Key::Field] a string, and [Key::MapKey] a map like `{"map_key": "PATH"}`;
+//!   in formats which are not human-readable (e.g. bincode), [Key] is an externally tagged enum instead,
+//!   as such formats cannot tell those apart
+//! - [Severity] is `"error"` or `"warning"`
+use std::{borrow::Cow, fmt::Formatter};
+
+use serde::{
+    de::{self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor},
+    ser::{SerializeMap, SerializeStruct},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{span::Span, Error, Failure, Key, KeyStr, Severity};
+
+const MAP_KEY: &str = "map_key";
+const KEY_VARIANTS: &[&str] = &["Index", "Field", "MapKey"];
+const SEVERITY_VARIANTS: &[&str] = &["warning", "error"];
+const FAILURE_FIELDS: &[&str] = &[
+    "path", "keys", "message", "severity", "code", "params", "source", "span",
+];
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Error {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self)
+    }
+}
+
+impl Serialize for Failure {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Failure", FAILURE_FIELDS.len())?;
+        s.serialize_field("path", &self.json_path())?;
+        s.serialize_field("keys", self.keys())?;
+        s.serialize_field("message", &self.message)?;
+        s.serialize_field("severity", &self.severity)?;
+        s.serialize_field("code", &self.code)?;
+        s.serialize_field("params", &Params(&self.params))?;
+        s.serialize_field("source", &self.source.as_ref().map(|e| e.to_string()))?;
+        s.serialize_field("span", &self.span)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Failure {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Failure", FAILURE_FIELDS, FailureVisitor)
+    }
+}
+
+struct Params<'a>(&'a [(Cow<'static, str>, String)]);
+
+impl Serialize for Params<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(k, v)| (k, v)))
+    }
+}
+
+/// Parameters, which keep their order when deserialized.
+struct ParamsBuf(Vec<(Cow<'static, str>, String)>);
+
+impl<'de> Deserialize<'de> for ParamsBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ParamsVisitor;
+
+        impl<'de> Visitor<'de> for ParamsVisitor {
+            type Value = ParamsBuf;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of parameters")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut params = Vec::new();
+                while let Some((k, v)) = map.next_entry::<String, String>()? {
+                    params.push((Cow::Owned(k), v));
+                }
+                Ok(ParamsBuf(params))
+            }
+        }
+
+        deserializer.deserialize_map(ParamsVisitor)
+    }
+}
+
+struct FailureVisitor;
+
+impl FailureVisitor {
+    fn build(
+        keys: Option<Vec<Key>>,
+        message: String,
+        severity: Option<Severity>,
+        code: Option<String>,
+        params: Option<ParamsBuf>,
+        source: Option<String>,
+        span: Option<Span>,
+    ) -> Failure {
+        let mut failure = Failure::new(&[], message).with_severity(severity.unwrap_or_default());
+        failure.key = keys.map(Into::into).unwrap_or_default();
+        failure.code = code.map(Into::into);
+        failure.params = params.map(|p| p.0).unwrap_or_default();
+        failure.span = span;
+        if let Some(source) = source {
+            failure = failure.with_source(source);
+        }
+        failure
+    }
+}
+
+impl<'de> Visitor<'de> for FailureVisitor {
+    type Value = Failure;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a validation failure")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let missing = |i| de::Error::invalid_length(i, &self);
+        let _path: String = seq.next_element()?.ok_or_else(|| missing(0))?;
+        let keys = seq.next_element()?.ok_or_else(|| missing(1))?;
+        let message = seq.next_element()?.ok_or_else(|| missing(2))?;
+        let severity = seq.next_element()?.ok_or_else(|| missing(3))?;
+        let code = seq.next_element()?.ok_or_else(|| missing(4))?;
+        let params = seq.next_element()?.ok_or_else(|| missing(5))?;
+        let source = seq.next_element()?.ok_or_else(|| missing(6))?;
+        let span = seq.next_element()?.ok_or_else(|| missing(7))?;
+        Ok(Self::build(
+            Some(keys),
+            message,
+            Some(severity),
+            code,
+            Some(params),
+            source,
+            span,
+        ))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut keys = None;
+        let mut message = None;
+        let mut severity = None;
+        let mut code = None;
+        let mut params = None;
+        let mut source = None;
+        let mut span = None;
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "keys" => keys = Some(map.next_value()?),
+                "message" => message = Some(map.next_value()?),
+                "severity" => severity = Some(map.next_value()?),
+                "code" => code = map.next_value()?,
+                "params" => params = Some(map.next_value()?),
+                "source" => source = map.next_value()?,
+                "span" => span = map.next_value()?,
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+        let message = message.ok_or_else(|| de::Error::missing_field("message"))?;
+        Ok(Self::build(
+            keys, message, severity, code, params, source, span,
+        ))
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return match self {
+                Key::Index(n) => {
+                    serializer.serialize_newtype_variant("Key", 0, KEY_VARIANTS[0], &(*n as u64))
+                }
+                Key::Field(s) => serializer.serialize_newtype_variant("Key", 1, KEY_VARIANTS[1], s),
+                Key::MapKey(s) => {
+                    serializer.serialize_newtype_variant("Key", 2, KEY_VARIANTS[2], s)
+                }
+            };
+        }
+        match self {
+            Key::Index(n) => serializer.serialize_u64(*n as u64),
+            Key::Field(s) => s.serialize(serializer),
+            Key::MapKey(s) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry(MAP_KEY, s)?;
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(KeyVisitor)
+        } else {
+            deserializer.deserialize_enum("Key", KEY_VARIANTS, KeyVisitor)
+        }
+    }
+}
+
+/// Deserializes the index of an enum variant, given by index or name.
+struct VariantIndex(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for VariantIndex {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl Visitor<'_> for VariantIndex {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        write!(formatter, "one of {:?}", self.0)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match usize::try_from(v) {
+            Ok(idx) if idx < self.0.len() => Ok(idx),
+            _ => Err(E::invalid_value(de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0
+            .iter()
+            .position(|name| *name == v)
+            .ok_or_else(|| E::unknown_variant(v, self.0))
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("an index, field name, or map key")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        usize::try_from(v)
+            .map(Key::Index)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        usize::try_from(v)
+            .map(Key::Index)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Key::from(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Key::from(v))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let Some(field) = map.next_key::<String>()? else {
+            return Err(de::Error::missing_field(MAP_KEY));
+        };
+        if field != MAP_KEY {
+            return Err(de::Error::unknown_field(&field, &[MAP_KEY]));
+        }
+        let key = Key::MapKey(map.next_value()?);
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+        Ok(key)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (idx, value) = data.variant_seed(VariantIndex(KEY_VARIANTS))?;
+        match idx {
+            0 => {
+                let n: u64 = value.newtype_variant()?;
+                self.visit_u64(n)
+            }
+            1 => value.newtype_variant().map(Key::Field),
+            _ => value.newtype_variant().map(Key::MapKey),
+        }
+    }
+}
+
+impl Serialize for KeyStr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyStr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl Serialize for Span {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Span", 3)?;
+        s.serialize_field("offset", &self.offset)?;
+        s.serialize_field("line", &self.line)?;
+        s.serialize_field("column", &self.column)?;
+        s.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SpanVisitor;
+
+        impl<'de> Visitor<'de> for SpanVisitor {
+            type Value = Span;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a span")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let offset = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let line = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let column = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                Ok(Span {
+                    offset,
+                    line,
+                    column,
+                })
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut offset, mut line, mut column) = (None, None, None);
+                while let Some(field) = map.next_key::<String>()? {
+                    match field.as_str() {
+                        "offset" => offset = Some(map.next_value()?),
+                        "line" => line = Some(map.next_value()?),
+                        "column" => column = Some(map.next_value()?),
+                        _ => {
+                            map.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(Span {
+                    offset: offset.ok_or_else(|| de::Error::missing_field("offset"))?,
+                    line: line.ok_or_else(|| de::Error::missing_field("line"))?,
+                    column: column.ok_or_else(|| de::Error::missing_field("column"))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct("Span", &["offset", "line", "column"], SpanVisitor)
+    }
+}
+
+impl Serialize for Severity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Severity::Warning => {
+                serializer.serialize_unit_variant("Severity", 0, SEVERITY_VARIANTS[0])
+            }
+            Severity::Error => {
+                serializer.serialize_unit_variant("Severity", 1, SEVERITY_VARIANTS[1])
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SeverityVisitor;
+
+        impl<'de> Visitor<'de> for SeverityVisitor {
+            type Value = Severity;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a severity")
+            }
+
+            fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+                let (idx, value) = data.variant_seed(VariantIndex(SEVERITY_VARIANTS))?;
+                value.unit_variant()?;
+                Ok(if idx == 0 {
+                    Severity::Warning
+                } else {
+                    Severity::Error
+                })
+            }
+        }
+
+        deserializer.deserialize_enum("Severity", SEVERITY_VARIANTS, SeverityVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{path, Accumulator, Error, Key, Severity};
+
+    fn error() -> Error {
+        let mut accum = Accumulator::new();
+        accum.with_key("env", |a| {
+            a.with_key(Key::map_key("PATH"), |a| {
+                a.add_failure_localized("required", [("var", "PATH")], "not set")
+            });
+            a.add_warning_at(0, "odd");
+            a.add_error_at("port", "x".parse::<u16>().unwrap_err());
+        });
+        crate::Result::from(accum).unwrap_err()
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut err = error();
+        err.locate(&|path: &[Key]| {
+            (path.last() == Some(&Key::from("port"))).then_some(crate::span::Span {
+                offset: 3,
+                line: 2,
+                column: 1,
+            })
+        });
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "path": "$.env[\"PATH\"]",
+                    "keys": ["env", {"map_key": "PATH"}],
+                    "message": "not set",
+                    "severity": "error",
+                    "code": "required",
+                    "params": {"var": "PATH"},
+                    "source": null,
+                    "span": null,
+                },
+                {
+                    "path": "$.env[0]",
+                    "keys": ["env", 0],
+                    "message": "odd",
+                    "severity": "warning",
+                    "code": null,
+                    "params": {},
+                    "source": null,
+                    "span": null,
+                },
+                {
+                    "path": "$.env.port",
+                    "keys": ["env", "port"],
+                    "message": "invalid digit found in string",
+                    "severity": "error",
+                    "code": null,
+                    "params": {},
+                    "source": "invalid digit found in string",
+                    "span": {"offset": 3, "line": 2, "column": 1},
+                },
+            ])
+        );
+        let de: Error = serde_json::from_value(json).unwrap();
+        assert_eq!(de.to_string(), err.to_string());
+        assert_eq!(de.failures()[0].code(), Some("required"));
+        assert_eq!(de.failures()[0].param("var"), Some("PATH"));
+        assert_eq!(de.failures()[2].span(), err.failures()[2].span());
+        assert_eq!(
+            de.failures()[2].source().unwrap().to_string(),
+            "invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn minimal() {
+        let de: Error = serde_json::from_str(r#"[{"message": "bad", "extra": 1}]"#).unwrap();
+        assert_eq!(de.failures()[0].keys(), path![]);
+        assert_eq!(de.failures()[0].severity(), Severity::Error);
+        assert!(serde_json::from_str::<Error>(r#"[{"keys": []}]"#).is_err());
+        assert!(serde_json::from_str::<Key>(r#"{"field": "a"}"#).is_err());
+        assert!(serde_json::from_str::<Key>("-1").is_err());
+    }
+
+    #[test]
+    fn compact() {
+        let keys = vec![Key::from("env"), Key::map_key("PATH"), Key::Index(3)];
+        let bytes = bincode::serialize(&keys).unwrap();
+        assert_eq!(bincode::deserialize::<Vec<Key>>(&bytes).unwrap(), keys);
+
+        let mut accum = Accumulator::new();
+        accum.with_key("env", |a| a.add_warning_at(Key::map_key("PATH"), "unset"));
+        accum.add_failure_at(0, "bad");
+        let err = crate::Result::from(accum).unwrap_err();
+        let de: Error = bincode::deserialize(&bincode::serialize(&err).unwrap()).unwrap();
+        assert_eq!(de.to_string(), err.to_string());
+    }
+}
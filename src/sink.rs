@@ -0,0 +1,602 @@
+//! Pluggable strategies for what an [Accumulator](crate::Accumulator) does with failures.
+//!
+//! By default, an accumulator collects every failure so that they can all be reported.
+//! [Accumulator::with_sink](crate::Accumulator::with_sink) swaps this for any [FailureSink],
+//! which allows trading detail for speed without changing any [Validate](crate::Validate) implementations.
+//!
+//! ```
+//! use validatrix::{sink::CountingSink, Accumulator, FailureSink, Validate};
+//!
+//! struct Even(u8);
+//!
+//! impl Validate for Even {
+//!     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+//!         if self.0 % 2 != 0 {
+//!             accum.add_failure(format!("{} is odd", self.0));
+//!         }
+//!     }
+//! }
+//!
+//! let values: Vec<_> = (0..10).map(Even).collect();
+//! let mut accum = Accumulator::with_sink(CountingSink::default());
+//! values.validate_inner(&mut accum);
+//! assert_eq!(accum.len(), 5);
+//! ```
+use crate::{
+    errors::{Path, Prefix},
+    Checkpoint, Failure, Key, Severity,
+};
+use alloc::{format, vec::Vec};
+
+/// A failure which has been reported to an [Accumulator](crate::Accumulator),
+/// but not yet built.
+///
+/// Building the [Failure] constructs its message and may copy its path;
+/// sinks which do not need those can skip that work.
+pub struct PendingFailure<'a> {
+    pub(crate) prefix: &'a mut Prefix,
+    pub(crate) severity: Severity,
+    pub(crate) build: &'a mut dyn FnMut(&Path) -> Failure,
+}
+
+impl<'a> PendingFailure<'a> {
+    /// Path to the failure.
+    pub fn path(&self) -> &[Key] {
+        self.prefix.keys()
+    }
+
+    /// How serious the failure is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Build the full failure.
+    pub fn into_failure(self) -> Failure {
+        (self.build)(self.prefix.path())
+    }
+
+    /// The path, shared with other failures at the same prefix.
+    pub(crate) fn shared_path(&mut self) -> Path {
+        self.prefix.path().clone()
+    }
+
+    /// Build the failure without copying the path into it,
+    /// for sinks which store the path separately.
+    ///
+    /// The failure's own keys (usually none) follow on from [Self::path].
+    #[cfg(feature = "arena")]
+    pub(crate) fn into_relative(self) -> (&'a [Key], Failure) {
+        let failure = (self.build)(&Path::default());
+        (self.prefix.keys(), failure)
+    }
+}
+
+/// Receives failures reported to an [Accumulator](crate::Accumulator).
+///
+/// The accumulator tracks paths, counts and limits itself;
+/// the sink only decides what to keep.
+pub trait FailureSink: Send {
+    /// Receive a failure.
+    fn record(&mut self, failure: PendingFailure<'_>);
+
+    /// Whether the sink will ignore any further failures,
+    /// in which case the accumulator skips any remaining validation.
+    fn is_full(&self) -> bool {
+        false
+    }
+
+    /// Remove and return the failures kept by this sink, for building an [Error](crate::Error).
+    fn take_failures(&mut self) -> Vec<Failure> {
+        Vec::new()
+    }
+
+    /// Discard kept failures so that only those recorded before the checkpoint remain,
+    /// as used by [Accumulator::rollback_to](crate::Accumulator::rollback_to).
+    ///
+    /// The sink has been given every failure recorded since it was last truncated or emptied,
+    /// so the first [Checkpoint::failures] of those (errors and warnings alike) should be kept.
+    ///
+    /// Sinks which cannot do this (e.g. because failures have already been streamed elsewhere)
+    /// may ignore it.
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        let _ = checkpoint;
+    }
+}
+
+/// Collects every failure; this is the default behaviour of an accumulator.
+impl FailureSink for Vec<Failure> {
+    fn record(&mut self, failure: PendingFailure<'_>) {
+        self.push(failure.into_failure())
+    }
+
+    /// Keeps this buffer's capacity, so that it can be reused (e.g. by an accumulator pool).
+    #[allow(clippy::drain_collect)]
+    fn take_failures(&mut self) -> Vec<Failure> {
+        self.drain(..).collect()
+    }
+
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        Vec::truncate(self, checkpoint.failures())
+    }
+}
+
+/// Counts failures without building them.
+///
+/// An [Error](crate::Error) produced from an accumulator using this sink
+/// contains a single failure summarising the count.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CountingSink {
+    /// Number of failures with [Severity::Error].
+    pub errors: usize,
+    /// Number of failures with [Severity::Warning].
+    pub warnings: usize,
+}
+
+impl FailureSink for CountingSink {
+    fn record(&mut self, failure: PendingFailure<'_>) {
+        match failure.severity() {
+            Severity::Error => self.errors += 1,
+            Severity::Warning => self.warnings += 1,
+        }
+    }
+
+    fn take_failures(&mut self) -> Vec<Failure> {
+        let count = core::mem::take(self);
+        summarise(Path::default(), count.errors, count.warnings)
+            .into_iter()
+            .collect()
+    }
+
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        self.errors = checkpoint.errors();
+        self.warnings = checkpoint.warnings();
+    }
+}
+
+/// A single failure summarising the counts, at the given path;
+/// a warning if there are no errors, and `None` if there are no failures at all.
+fn summarise(path: Path, errors: usize, warnings: usize) -> Option<Failure> {
+    let message = match (errors, warnings) {
+        (0, 0) => return None,
+        (0, w) => {
+            return Some(
+                Failure::new_at(path, format!("{w} warning(s)")).with_severity(Severity::Warning),
+            )
+        }
+        (e, 0) => format!("{e} error(s)"),
+        (e, w) => format!("{e} error(s) and {w} warning(s)"),
+    };
+    Some(Failure::new_at(path, message))
+}
+
+/// Counts failures and remembers where the first error was, without building any failures.
+///
+/// Used by [Accumulator::counting](crate::Accumulator::counting),
+/// for callers which only need to know whether a value is valid and a headline for the log.
+/// An [Error](crate::Error) produced from an accumulator using this sink
+/// contains a single failure at the first error's path (or the root, if there are only warnings),
+/// summarising the counts.
+#[derive(Debug, Default, Clone)]
+pub struct SummarySink {
+    errors: usize,
+    warnings: usize,
+    first_path: Option<Path>,
+    /// Index of the first error among all the failures recorded.
+    first_index: usize,
+}
+
+impl SummarySink {
+    /// Number of failures with [Severity::Error].
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Number of failures with [Severity::Warning].
+    pub fn warnings(&self) -> usize {
+        self.warnings
+    }
+
+    /// Path to the first failure with [Severity::Error], if there was one.
+    pub fn first_path(&self) -> Option<&[Key]> {
+        self.first_path.as_deref()
+    }
+}
+
+impl FailureSink for SummarySink {
+    fn record(&mut self, mut failure: PendingFailure<'_>) {
+        match failure.severity() {
+            Severity::Error => {
+                if self.first_path.is_none() {
+                    self.first_path = Some(failure.shared_path());
+                    self.first_index = self.errors + self.warnings;
+                }
+                self.errors += 1;
+            }
+            Severity::Warning => self.warnings += 1,
+        }
+    }
+
+    fn take_failures(&mut self) -> Vec<Failure> {
+        let summary = core::mem::take(self);
+        let path = summary.first_path.unwrap_or_default();
+        summarise(path, summary.errors, summary.warnings)
+            .into_iter()
+            .collect()
+    }
+
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        if self.first_index >= checkpoint.failures() {
+            self.first_path = None;
+        }
+        self.errors = checkpoint.errors();
+        self.warnings = checkpoint.warnings();
+    }
+}
+
+/// Keeps only the first failure with [Severity::Error], then stops validation.
+///
+/// Warnings are ignored.
+#[derive(Debug, Default)]
+pub struct FirstFailureSink {
+    failure: Option<Failure>,
+    /// Index of the kept failure among all those recorded.
+    index: usize,
+    /// Number of failures recorded, including warnings.
+    seen: usize,
+}
+
+impl FirstFailureSink {
+    /// The failure kept so far, if any.
+    pub fn failure(&self) -> Option<&Failure> {
+        self.failure.as_ref()
+    }
+}
+
+impl FailureSink for FirstFailureSink {
+    fn record(&mut self, failure: PendingFailure<'_>) {
+        if self.failure.is_none() && failure.severity() == Severity::Error {
+            self.index = self.seen;
+            self.failure = Some(failure.into_failure());
+        }
+        self.seen += 1;
+    }
+
+    fn is_full(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    fn take_failures(&mut self) -> Vec<Failure> {
+        core::mem::take(self).failure.into_iter().collect()
+    }
+
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        if self.index >= checkpoint.failures() {
+            self.failure = None;
+        }
+        self.seen = checkpoint.failures();
+    }
+}
+
+/// Keeps no failures and builds nothing; only the [Accumulator](crate::Accumulator)'s counts are kept.
+///
+/// Used by [Validate::is_valid](crate::Validate::is_valid).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiscardSink;
+
+impl FailureSink for DiscardSink {
+    fn record(&mut self, _failure: PendingFailure<'_>) {}
+}
+
+/// Passes each failure to a callback as soon as it is reported, e.g. for logging.
+///
+/// Failures are not kept, so an [Error](crate::Error) produced from an accumulator using this sink
+/// contains no failures.
+pub struct CallbackSink<F>(pub F);
+
+impl<F: FnMut(Failure) + Send> FailureSink for CallbackSink<F> {
+    fn record(&mut self, failure: PendingFailure<'_>) {
+        (self.0)(failure.into_failure())
+    }
+}
+
+impl<F> core::fmt::Debug for CallbackSink<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("CallbackSink(..)")
+    }
+}
+
+/// Keeps up to `N` failures in fixed-capacity storage, counting any more without building them.
+///
+/// Used by [Accumulator::fixed](crate::Accumulator::fixed).
+/// Validation continues past capacity, so that the overflow count is accurate.
+/// Building failures with `&'static str` messages and short paths does not allocate,
+/// so the kept failures can be inspected (with [Accumulator::sink](crate::Accumulator::sink))
+/// without any dynamic allocation, as the sink is stored inline in the accumulator.
+///
+/// An [Error](crate::Error) produced from an accumulator using this sink
+/// contains the kept failures, followed by a summary of the overflow if there was any.
+#[cfg(feature = "heapless")]
+#[derive(Debug, Default)]
+pub struct FixedSink<const N: usize> {
+    failures: heapless::Vec<Failure, N>,
+    overflow: usize,
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> FixedSink<N> {
+    /// Create an empty sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The failures kept so far, in the order they were recorded.
+    pub fn failures(&self) -> &[Failure] {
+        &self.failures
+    }
+
+    /// Number of failures recorded after the sink was at capacity.
+    pub fn overflow(&self) -> usize {
+        self.overflow
+    }
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize> FailureSink for FixedSink<N> {
+    fn record(&mut self, failure: PendingFailure<'_>) {
+        if self.failures.is_full() {
+            self.overflow += 1;
+            return;
+        }
+        // cannot fail, as there is space
+        let _ = self.failures.push(failure.into_failure());
+    }
+
+    fn take_failures(&mut self) -> Vec<Failure> {
+        let kept = core::mem::take(self);
+        let mut failures: Vec<_> = kept.failures.into_iter().collect();
+        if kept.overflow > 0 {
+            failures.push(Failure::new(
+                &[],
+                format!("{} more failure(s) not recorded", kept.overflow),
+            ));
+        }
+        failures
+    }
+
+    fn truncate(&mut self, checkpoint: &Checkpoint) {
+        let len = checkpoint.failures();
+        match len.checked_sub(self.failures.len()) {
+            Some(overflow) => self.overflow = overflow,
+            None => {
+                self.failures.truncate(len);
+                self.overflow = 0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Accumulator, Validate};
+
+    struct Even(u8);
+
+    impl Validate for Even {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if self.0 % 2 != 0 {
+                accum.add_failure(format!("{} is odd", self.0));
+            }
+            if self.0 > 5 {
+                accum.add_warning("large");
+            }
+        }
+    }
+
+    fn values() -> Vec<Even> {
+        (0..10).map(Even).collect()
+    }
+
+    #[test]
+    fn counting() {
+        let mut accum = Accumulator::with_sink(CountingSink::default());
+        values().validate_inner(&mut accum);
+        assert_eq!(accum.len(), 9);
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $: 5 error(s) and 4 warning(s)"
+        );
+    }
+
+    #[test]
+    fn counting_is_lazy() {
+        let mut accum = Accumulator::with_sink(CountingSink::default());
+        accum.add_failure_lazy_at("a", || unreachable!("message should not be built"));
+        accum.add_failure_fmt(format_args!("{}", Panics));
+        assert_eq!(accum.len(), 2);
+
+        struct Panics;
+
+        impl std::fmt::Display for Panics {
+            fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                unreachable!("message should not be formatted")
+            }
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let mut accum = Accumulator::counting();
+        accum.with_key("values", |a| values().validate_inner(a));
+        accum.add_failure_lazy_at("a", || unreachable!("message should not be built"));
+        assert_eq!(accum.len(), 10);
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $.values[1]: 6 error(s) and 4 warning(s)"
+        );
+    }
+
+    #[test]
+    fn summary_rollback() {
+        let mut accum = Accumulator::counting();
+        accum.add_failure_at("a", "kept");
+        let cp = accum.checkpoint();
+        accum.add_failure_at("b", "rolled back");
+        accum.add_failure_at("c", "rolled back");
+        accum.rollback_to(cp);
+        assert_eq!(accum.sink().errors(), 1);
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $.a: 1 error(s)"
+        );
+
+        let mut accum = Accumulator::counting();
+        accum.add_warning_at("a", "kept");
+        let cp = accum.checkpoint();
+        accum.add_failure_at("b", "rolled back");
+        accum.rollback_to(cp);
+        accum.add_failure_at("c", "kept");
+        assert_eq!(accum.sink().first_path(), Some(&[Key::from("c")][..]));
+    }
+
+    #[test]
+    fn summarise_warnings() {
+        let mut accum = Accumulator::counting();
+        accum.set_fail_on_warnings(true);
+        accum.add_warning_at("a", "iffy");
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $ (warning): 1 warning(s)"
+        );
+
+        let mut accum = Accumulator::with_sink(CountingSink::default());
+        accum.set_fail_on_warnings(true);
+        accum.add_warning_at("a", "iffy");
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $ (warning): 1 warning(s)"
+        );
+    }
+
+    #[test]
+    fn first() {
+        let mut accum = Accumulator::with_sink(FirstFailureSink::default());
+        values().validate_inner(&mut accum);
+        assert!(accum.is_full());
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(err.to_string(), "Validation failure(s):\n   $[1]: 1 is odd");
+    }
+
+    #[test]
+    fn callback() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut accum = Accumulator::with_sink(CallbackSink(move |f: Failure| {
+            tx.send(f.to_string()).unwrap();
+        }));
+        values().validate_inner(&mut accum);
+        assert!(crate::Result::from(accum).is_err());
+        let msgs: Vec<_> = rx.iter().collect();
+        assert_eq!(msgs.len(), 9);
+        assert_eq!(msgs[0], "$[1]: 1 is odd");
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn fixed() {
+        let mut accum = Accumulator::fixed::<2>();
+        values().validate_inner(&mut accum);
+        assert_eq!(accum.len(), 9);
+        let sink = accum.sink();
+        assert_eq!(sink.failures().len(), 2);
+        assert_eq!(sink.overflow(), 7);
+
+        let cp = accum.checkpoint();
+        accum.add_failure("tentative");
+        accum.rollback_to(cp);
+        assert_eq!(accum.sink().overflow(), 7);
+
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $[1]: 1 is odd\n   $[3]: 3 is odd\n   $: 7 more failure(s) not recorded"
+        );
+    }
+
+    /// Warning, then checkpoint, then an error which is rolled back, then another error.
+    fn warning_then_rollback<S: FailureSink>(mut accum: Accumulator<S>) -> Accumulator<S> {
+        accum.add_warning("iffy");
+        let cp = accum.checkpoint();
+        accum.add_failure_at("a", "rolled back");
+        accum.rollback_to(cp);
+        assert!(!accum.is_full());
+        accum.add_failure_at("b", "kept");
+        accum
+    }
+
+    #[test]
+    fn rollback_after_warning() {
+        let err = crate::Result::from(warning_then_rollback(Accumulator::new())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $ (warning): iffy\n   $.b: kept"
+        );
+
+        let accum = warning_then_rollback(Accumulator::with_sink(CountingSink::default()));
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $: 1 error(s) and 1 warning(s)"
+        );
+
+        let accum = warning_then_rollback(Accumulator::counting());
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):\n   $.b: 1 error(s) and 1 warning(s)"
+        );
+
+        let accum = warning_then_rollback(Accumulator::with_sink(FirstFailureSink::default()));
+        assert!(accum.is_full());
+        let err = crate::Result::from(accum).unwrap_err();
+        assert_eq!(err.to_string(), "Validation failure(s):\n   $.b: kept");
+
+        let accum = warning_then_rollback(Accumulator::with_sink(DiscardSink));
+        assert_eq!(accum.len(), 2);
+        assert!(crate::Result::from(accum).is_err());
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let accum =
+            warning_then_rollback(Accumulator::with_sink(CallbackSink(move |f: Failure| {
+                tx.send(f.to_string()).unwrap();
+            })));
+        assert!(crate::Result::from(accum).is_err());
+        // already streamed, so cannot be rolled back
+        assert_eq!(rx.iter().count(), 3);
+
+        #[cfg(feature = "heapless")]
+        {
+            let accum = warning_then_rollback(Accumulator::fixed::<2>());
+            let err = crate::Result::from(accum).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Validation failure(s):\n   $ (warning): iffy\n   $.b: kept"
+            );
+        }
+    }
+
+    #[test]
+    fn rollback() {
+        let mut accum = Accumulator::with_sink(FirstFailureSink::default());
+        let cp = accum.checkpoint();
+        accum.add_failure("tentative");
+        assert!(accum.is_full());
+        accum.rollback_to(cp);
+        assert!(!accum.is_full());
+        assert!(crate::Result::from(accum).is_ok());
+    }
+}
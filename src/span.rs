@@ -0,0 +1,296 @@
+//! Locating failures in the source text they were deserialized from.
+//!
+//! Serde deserializers do not report where each value came from,
+//! so spans are attached after validation by a [Locate] implementation
+//! which finds a path in the source text, using [Error::locate](crate::Error::locate).
+//! [Json] does this for JSON; other formats can implement [Locate] themselves.
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use validatrix::{span::{Json, Span}, Accumulator, FailureSink, Valid, Validate};
+//!
+//! #[derive(Debug, serde::Deserialize)]
+//! struct Server {
+//!     port: u16,
+//! }
+//!
+//! impl Validate for Server {
+//!     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+//!         if self.port < 1024 {
+//!             accum.add_failure_at("port", "must not be privileged");
+//!         }
+//!     }
+//! }
+//!
+//! let text = "{\n  \"port\": 80\n}";
+//! let server: Server = serde_json::from_str(text).unwrap();
+//! let mut err = Valid::try_new(server).unwrap_err();
+//! err.locate(&Json(text));
+//! let span = err.failures()[0].span().unwrap();
+//! assert_eq!((span.line, span.column), (2, 11));
+//! # }
+//! ```
+use crate::Key;
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+/// Location of a value in source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Span {
+    /// Byte offset of the start of the value.
+    pub offset: usize,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in characters, starting at 1.
+    pub column: usize,
+}
+
+impl Span {
+    /// Find the line and column of a byte offset into the text.
+    ///
+    /// An offset inside a character or past the end of the text
+    /// is moved back to the previous character boundary.
+    pub fn from_offset(text: &str, offset: usize) -> Self {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        Self {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl core::fmt::Display for Span {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Finds the location of a path in some source text.
+pub trait Locate {
+    /// The location of the value at the given path, if it can be found.
+    fn locate(&self, path: &[Key]) -> Option<Span>;
+}
+
+impl<F: Fn(&[Key]) -> Option<Span>> Locate for F {
+    fn locate(&self, path: &[Key]) -> Option<Span> {
+        self(path)
+    }
+}
+
+/// Locates values in JSON text.
+///
+/// [Key::Field] and [Key::MapKey] both match object members, and [Key::Index] matches array elements.
+/// If an object has duplicate members, the first is used.
+/// Malformed JSON may not be located.
+#[derive(Debug, Clone, Copy)]
+pub struct Json<'a>(pub &'a str);
+
+impl Locate for Json<'_> {
+    fn locate(&self, path: &[Key]) -> Option<Span> {
+        let mut scanner = Scanner {
+            bytes: self.0.as_bytes(),
+            pos: 0,
+        };
+        scanner.skip_ws();
+        for key in path {
+            match (scanner.peek()?, key) {
+                (b'{', Key::Field(name) | Key::MapKey(name)) => scanner.find_member(name)?,
+                (b'[', Key::Index(idx)) => scanner.find_element(*idx)?,
+                _ => return None,
+            }
+        }
+        Some(Span::from_offset(self.0, scanner.pos))
+    }
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.skip_ws();
+        if self.peek()? != byte {
+            return None;
+        }
+        self.pos += 1;
+        self.skip_ws();
+        Some(())
+    }
+
+    /// Move to the value of the named member of the object at the current position.
+    fn find_member(&mut self, name: &str) -> Option<()> {
+        self.expect(b'{')?;
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            if key == name {
+                return Some(());
+            }
+            self.skip_value()?;
+            self.expect(b',')?;
+        }
+    }
+
+    /// Move to the given element of the array at the current position.
+    fn find_element(&mut self, idx: usize) -> Option<()> {
+        self.expect(b'[')?;
+        if self.peek()? == b']' {
+            return None;
+        }
+        for _ in 0..idx {
+            self.skip_value()?;
+            self.expect(b',')?;
+        }
+        Some(())
+    }
+
+    /// Parse the string at the current position.
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.skip_string()?;
+        let raw = core::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+        if !raw.contains('\\') {
+            return Some(raw[1..raw.len() - 1].to_string());
+        }
+        crate::Key::parse_path(&format!("[{raw}]"))
+            .ok()
+            .and_then(|mut keys| match keys.pop() {
+                Some(Key::MapKey(s)) => Some(s.as_str().to_string()),
+                _ => None,
+            })
+    }
+
+    fn skip_string(&mut self) -> Option<()> {
+        if self.peek()? != b'"' {
+            return None;
+        }
+        self.pos += 1;
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.pos += 1;
+                    return Some(());
+                }
+                b'\\' => self.pos += 2,
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek()? {
+                b'"' => self.skip_string()?,
+                b'{' | b'[' => {
+                    depth += 1;
+                    self.pos += 1;
+                }
+                b'}' | b']' if depth == 0 => return Some(()),
+                b'}' | b']' => {
+                    depth -= 1;
+                    self.pos += 1;
+                }
+                b',' if depth == 0 => return Some(()),
+                _ => self.pos += 1,
+            }
+            if depth == 0 {
+                self.skip_ws();
+                if matches!(self.peek(), Some(b',' | b'}' | b']') | None) {
+                    return Some(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path;
+
+    const TEXT: &str = r#"{
+  "name": "x",
+  "nested": {"a\"b": [1, {"c": [true]}, "é"], "d": null},
+  "list": [
+    [],
+    {"e": "f,]}"},
+    3
+  ]
+}"#;
+
+    fn locate(path: &[Key]) -> Option<(usize, usize)> {
+        Json(TEXT).locate(path).map(|s| (s.line, s.column))
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(locate(&[]), Some((1, 1)));
+        assert_eq!(locate(&path!["name"]), Some((2, 11)));
+        assert_eq!(
+            locate(&path!["nested", Key::map_key("a\"b")]),
+            Some((3, 22))
+        );
+        assert_eq!(
+            locate(&path!["nested", Key::map_key("a\"b"), 1, "c", 0]),
+            Some((3, 33))
+        );
+        assert_eq!(
+            locate(&path!["nested", Key::map_key("a\"b"), 2]),
+            Some((3, 41))
+        );
+        assert_eq!(locate(&path!["nested", "d"]), Some((3, 52)));
+        assert_eq!(locate(&path!["list", 0]), Some((5, 5)));
+        assert_eq!(locate(&path!["list", 2]), Some((7, 5)));
+        assert_eq!(locate(&path!["list", 1, "e"]), Some((6, 11)));
+    }
+
+    #[test]
+    fn clamped_offset() {
+        let span = Span::from_offset("a\né", 3);
+        assert_eq!((span.offset, span.line, span.column), (2, 2, 1));
+        let span = Span::from_offset("ab", 10);
+        assert_eq!((span.offset, span.line, span.column), (2, 1, 3));
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(locate(&path!["missing"]), None);
+        assert_eq!(locate(&path!["list", 3]), None);
+        assert_eq!(locate(&path!["list", 0, 0]), None);
+        assert_eq!(locate(&path!["name", "x"]), None);
+        assert_eq!(locate(&path!["list", "x"]), None);
+    }
+
+    #[test]
+    fn attach() {
+        let mut accum = crate::Accumulator::new();
+        accum.with_keys(&path!["list", 2], |a| a.add_failure("bad"));
+        accum.add_failure_at("missing", "bad");
+        let mut err = crate::Result::from(accum).unwrap_err();
+        err.locate(&Json(TEXT));
+        assert_eq!(err.failures()[0].span().unwrap().to_string(), "7:5");
+        assert_eq!(err.failures()[1].span(), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! Validated values which record when, and against which version of their context,
+//! they were validated.
+use std::{
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+use crate::{Valid, ValidateContext};
+
+/// A [Valid] value which records when it was validated,
+/// and the version of the context it was validated against.
+///
+/// Useful for long-lived values whose context can change,
+/// e.g. if the context holds IDs of database rows which may be deleted.
+/// The caller decides how contexts are versioned, e.g. with a counter incremented on every change.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use validatrix::{Accumulator, FailureSink, RevalidationPolicy, Stamped, ValidateContext};
+///
+/// struct Order {
+///     product: u32,
+/// }
+///
+/// impl ValidateContext for Order {
+///     type Context = HashSet<u32>;
+///
+///     fn validate_inner_ctx<S: FailureSink>(&self, accum: &mut Accumulator<S>, products: &HashSet<u32>) {
+///         if !products.contains(&self.product) {
+///             accum.add_failure_at("product", "does not exist");
+///         }
+///     }
+/// }
+///
+/// let mut products = HashSet::from([1, 2]);
+/// let mut order = Stamped::try_new_ctx(Order { product: 1 }, &products, 1).unwrap();
+///
+/// products.remove(&1);
+/// let policy = RevalidationPolicy::new().with_version(2);
+/// assert!(order.is_stale(&policy));
+/// assert!(order.revalidate(&products, 2).is_err());
+/// assert!(order.is_stale(&policy));
+/// assert_eq!(order.version(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stamped<T> {
+    valid: Valid<T>,
+    validated_at: Instant,
+    version: u64,
+}
+
+impl<T: ValidateContext> Stamped<T> {
+    /// Validate the value with the given context, recording the context's version.
+    pub fn try_new_ctx(inner: T, context: &T::Context, version: u64) -> crate::Result<Self> {
+        Ok(Self {
+            valid: Valid::try_new_ctx(inner, context)?,
+            validated_at: Instant::now(),
+            version,
+        })
+    }
+
+    /// Validate the value again with the given context.
+    ///
+    /// If it is valid, the time and version are updated.
+    /// If not, they are left as they were:
+    /// the value was valid at that time and version, and [Self::is_stale] still reflects that.
+    pub fn revalidate(&mut self, context: &T::Context, version: u64) -> crate::Result {
+        self.valid.validate_ctx(context)?;
+        self.validated_at = Instant::now();
+        self.version = version;
+        Ok(())
+    }
+}
+
+impl<T> Stamped<T> {
+    /// Record that an already-validated value was validated now, against the given context version.
+    pub fn new(valid: Valid<T>, version: u64) -> Self {
+        Self {
+            valid,
+            validated_at: Instant::now(),
+            version,
+        }
+    }
+
+    /// When the value was last successfully validated.
+    pub fn validated_at(&self) -> Instant {
+        self.validated_at
+    }
+
+    /// The version of the context the value was last successfully validated against.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Whether the value should be revalidated according to the policy.
+    pub fn is_stale(&self, policy: &RevalidationPolicy) -> bool {
+        policy
+            .max_age
+            .is_some_and(|max_age| self.validated_at.elapsed() > max_age)
+            || policy.version.is_some_and(|version| self.version < version)
+    }
+
+    /// Borrow the validated value.
+    pub fn valid(&self) -> &Valid<T> {
+        &self.valid
+    }
+
+    /// Discard the metadata, returning the validated value.
+    pub fn into_valid(self) -> Valid<T> {
+        self.valid
+    }
+}
+
+impl<T> Deref for Stamped<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.valid
+    }
+}
+
+/// When a [Stamped] value should be revalidated.
+///
+/// By default, values are never stale.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RevalidationPolicy {
+    max_age: Option<Duration>,
+    version: Option<u64>,
+}
+
+impl RevalidationPolicy {
+    /// A policy under which values are never stale.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Values are stale if they were validated longer ago than this.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Values are stale if they were validated against an older version of the context than this,
+    /// i.e. this is the current version.
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = Some(version);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{synch::NoContext, Accumulator, FailureSink, Validate};
+
+    struct Flag(bool);
+
+    impl Validate for Flag {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            if !self.0 {
+                accum.add_failure("unset");
+            }
+        }
+    }
+
+    #[test]
+    fn policy() {
+        let mut stamped = Stamped::try_new_ctx(NoContext(Flag(true)), &(), 3).unwrap();
+        assert!(!stamped.is_stale(&RevalidationPolicy::new()));
+        assert!(!stamped.is_stale(&RevalidationPolicy::new().with_version(3)));
+        assert!(stamped.is_stale(&RevalidationPolicy::new().with_version(4)));
+        std::thread::sleep(Duration::from_millis(2));
+        let policy = RevalidationPolicy::new().with_max_age(Duration::from_millis(1));
+        assert!(stamped.is_stale(&policy));
+
+        stamped.revalidate(&(), 4).unwrap();
+        assert_eq!(stamped.version(), 4);
+        assert!(!stamped.is_stale(&RevalidationPolicy::new().with_version(4)));
+        assert!(stamped.into_valid().0 .0);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::errors::Accumulator;
+use crate::{errors::Accumulator, FailureSink};
 
 /// Trait for synchronous validation.
 pub trait Validate {
@@ -8,16 +8,58 @@ pub trait Validate {
     /// use [Validate::validate_inner] instead.
     /// Should not be overridden by implementors.
     fn validate(&self) -> crate::Result {
-        let mut accum = Accumulator::new();
+        crate::telemetry::timed::<Self>(|| {
+            let mut accum = Accumulator::new();
+            self.validate_inner(&mut accum);
+            accum
+        })
+    }
+
+    /// Check whether this value is valid, without reporting why not.
+    ///
+    /// Stops at the first failure and does not build failure messages,
+    /// so may be much faster than [Validate::validate] for invalid values.
+    /// Should not be overridden by implementors.
+    fn is_valid(&self) -> bool {
+        let mut accum = Accumulator::validity();
         self.validate_inner(&mut accum);
-        accum.into()
+        !accum.has_errors()
     }
 
     /// Accumulate validation errors.
     ///
     /// Validators of containing types should call this;
     /// end users probably want [Validate::validate] instead.
-    fn validate_inner(&self, accum: &mut Accumulator);
+    /// Generic over the accumulator's [FailureSink],
+    /// so that the same implementation can collect, count or discard failures.
+    fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>);
+}
+
+/// Trait for synchronous validation against one of several sets of rules,
+/// identified by a profile type `P`.
+///
+/// For example, a type could be validated differently when it is created and when it is updated,
+/// using marker types `Create` and `Update` as profiles.
+/// [Valid](crate::Valid) records the profile in its type; see there for an example.
+pub trait ValidateProfile<P> {
+    /// Perform top-level validation on this value, using the rules for the profile.
+    ///
+    /// Should not be called inside other validators;
+    /// use [ValidateProfile::validate_inner_profile] instead.
+    /// Should not be overridden by implementors.
+    fn validate_profile(&self) -> crate::Result {
+        crate::telemetry::timed::<Self>(|| {
+            let mut accum = Accumulator::new();
+            self.validate_inner_profile(&mut accum);
+            accum
+        })
+    }
+
+    /// Accumulate validation errors, using the rules for the profile.
+    ///
+    /// Validators of containing types should call this;
+    /// end users probably want [ValidateProfile::validate_profile] instead.
+    fn validate_inner_profile<S: FailureSink>(&self, accum: &mut Accumulator<S>);
 }
 
 /// Trait for synchronous validation where some external data or context is required.
@@ -31,16 +73,114 @@ pub trait ValidateContext {
     /// use [ValidateContext::validate_inner] instead.
     /// Should not be overridden by implementors.
     fn validate_ctx(&self, context: &Self::Context) -> crate::Result {
-        let mut accum = Accumulator::new();
+        crate::telemetry::timed::<Self>(|| {
+            let mut accum = Accumulator::new();
+            self.validate_inner_ctx(&mut accum, context);
+            accum
+        })
+    }
+
+    /// Like [Validate::is_valid], with the given context.
+    ///
+    /// Should not be overridden by implementors.
+    fn is_valid_ctx(&self, context: &Self::Context) -> bool {
+        let mut accum = Accumulator::validity();
         self.validate_inner_ctx(&mut accum, context);
-        accum.into()
+        !accum.has_errors()
     }
 
     /// Accumulate validation errors.
     ///
     /// Validators of containing types should call this;
     /// end users probably want [ValidateContext::validate] instead.
-    fn validate_inner_ctx(&self, accum: &mut Accumulator, context: &Self::Context);
+    fn validate_inner_ctx<S: FailureSink>(
+        &self,
+        accum: &mut Accumulator<S>,
+        context: &Self::Context,
+    );
+}
+
+/// Adapter which lets a [Validate] type be used where a [ValidateContext] is expected.
+///
+/// The context is `()`, and is ignored.
+///
+/// ```
+/// use validatrix::{synch::NoContext, Accumulator, FailureSink, Validate, ValidateContext};
+///
+/// fn validate_all<V: ValidateContext>(items: &[V], context: &V::Context) -> validatrix::Result {
+///     items.iter().try_for_each(|item| item.validate_ctx(context))
+/// }
+///
+/// struct Positive(i32);
+///
+/// impl Validate for Positive {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 <= 0 {
+///             accum.add_failure("not positive");
+///         }
+///     }
+/// }
+///
+/// let items = [NoContext(Positive(1)), NoContext(Positive(-1))];
+/// assert!(validate_all(&items, &()).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NoContext<T>(pub T);
+
+impl<T: Validate> ValidateContext for NoContext<T> {
+    type Context = ();
+
+    fn validate_inner_ctx<S: FailureSink>(
+        &self,
+        accum: &mut Accumulator<S>,
+        _context: &Self::Context,
+    ) {
+        self.0.validate_inner(accum)
+    }
+}
+
+/// Validate the target of a [Deref](std::ops::Deref) type as though it were the value itself.
+///
+/// Useful for implementing [Validate] on custom smart pointers and wrappers.
+///
+/// ```
+/// use std::ops::Deref;
+/// use validatrix::{synch::validate_deref, Accumulator, FailureSink, Validate};
+///
+/// struct Positive(i32);
+///
+/// impl Validate for Positive {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0 <= 0 {
+///             accum.add_failure("not positive");
+///         }
+///     }
+/// }
+///
+/// struct Wrapper(Positive);
+///
+/// impl Deref for Wrapper {
+///     type Target = Positive;
+///
+///     fn deref(&self) -> &Positive {
+///         &self.0
+///     }
+/// }
+///
+/// impl Validate for Wrapper {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         validate_deref(self, accum)
+///     }
+/// }
+///
+/// assert!(Wrapper(Positive(-1)).validate().is_err());
+/// ```
+pub fn validate_deref<D, S: FailureSink>(value: &D, accum: &mut Accumulator<S>)
+where
+    D: core::ops::Deref + ?Sized,
+    D::Target: Validate,
+{
+    value.deref().validate_inner(accum)
 }
 
 #[cfg(test)]
@@ -55,7 +195,7 @@ mod tests {
     }
 
     impl Validate for A {
-        fn validate_inner(&self, accum: &mut errors::Accumulator) {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut errors::Accumulator<S>) {
             if self.avalue % 2 != 0 {
                 accum.add_failure_at("avalue", "value is odd");
             }
@@ -70,7 +210,7 @@ mod tests {
     }
 
     impl Validate for B {
-        fn validate_inner(&self, accum: &mut errors::Accumulator) {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut errors::Accumulator<S>) {
             if self.bvalue % 2 != 0 {
                 accum.add_failure_at("bvalue", "value is odd");
             }
@@ -84,7 +224,7 @@ mod tests {
     }
 
     impl Validate for C {
-        fn validate_inner(&self, accum: &mut errors::Accumulator) {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut errors::Accumulator<S>) {
             if self.cvalue % 2 != 0 {
                 accum.add_failure_at("cvalue", "value is odd");
             }
@@ -114,6 +254,53 @@ mod tests {
         };
         let err = valid.validate().unwrap_err();
         println!("{err}");
+        assert_eq!(err.len(), 4);
+        assert!(!valid.is_valid());
+    }
+
+    struct E {
+        deprecated: Option<u8>,
+        value: u8,
+    }
+
+    impl Validate for E {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut errors::Accumulator<S>) {
+            if self.deprecated.is_some() {
+                accum.add_warning_at("deprecated", "field is deprecated");
+            }
+            if self.value % 2 != 0 {
+                accum.add_failure_at("value", "value is odd");
+            }
+        }
+    }
+
+    #[test]
+    fn warnings() {
+        let only_warning = E {
+            deprecated: Some(1),
+            value: 0,
+        };
+        assert!(only_warning.validate().is_ok());
+
+        let mut accum = Accumulator::new();
+        only_warning.validate_inner(&mut accum);
+        assert_eq!(accum.warnings().count(), 1);
+        assert!(!accum.has_errors());
+
+        let err = E {
+            deprecated: Some(1),
+            value: 1,
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(err.errors().count(), 1);
+        assert_eq!(err.warnings().count(), 1);
+        assert_eq!(
+            err.to_string(),
+            "Validation failure(s):
+   $.deprecated (warning): field is deprecated
+   $.value: value is odd"
+        );
     }
 
     struct DContext {
@@ -125,7 +312,11 @@ mod tests {
     impl ValidateContext for D {
         type Context = DContext;
 
-        fn validate_inner_ctx(&self, accum: &mut errors::Accumulator, context: &Self::Context) {
+        fn validate_inner_ctx<S: FailureSink>(
+            &self,
+            accum: &mut errors::Accumulator<S>,
+            context: &Self::Context,
+        ) {
             for (i, v) in self.0.iter().enumerate() {
                 if *v > context.threshold {
                     accum.add_failure_at(
@@ -145,5 +336,7 @@ mod tests {
 
         let d_invalid = D(vec![0, 1, 4]);
         assert!(d_invalid.validate_ctx(&ctx).is_err());
+        assert!(d_valid.is_valid_ctx(&ctx));
+        assert!(!d_invalid.is_valid_ctx(&ctx));
     }
 }
@@ -0,0 +1,177 @@
+//! Recording metrics about validation outcomes with the [metrics](https://docs.rs/metrics) crate.
+//!
+//! Whenever a value is validated at the top level
+//! (with `validate`, `validate_ctx` or their async equivalents), the following are recorded:
+//!
+//! - `validatrix_validations_total`: counter, labelled with the validated `type` and the `outcome`
+//!   (`valid` or `invalid`)
+//! - `validatrix_failures_total`: counter, labelled with the validated `type`,
+//!   the failure's `severity`, `code` (empty if none)
+//!   and top-level `key` (a static field name, `*` for other field names,
+//!   `[]` for indices and map keys, or `$` for the root);
+//!   warnings are counted even if the outcome is `valid`
+//!
+//! Field names built at runtime, including those renamed with a [RenameRule](crate::RenameRule),
+//! are all labelled `*` to keep the number of label values bounded,
+//! so per-field counts are only available for names written as `&'static str`.
+//! - `validatrix_validation_duration_seconds`: histogram, labelled with the validated `type`
+//!
+//! Install a recorder from an exporter crate to collect them.
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+use crate::Accumulator;
+#[cfg(feature = "metrics")]
+use crate::{Key, Severity};
+
+/// Run a top-level validation of a `T`, recording metrics about it if the `metrics` feature is enabled.
+///
+/// `validate` returns the accumulator rather than the result,
+/// so that warnings are counted even if the result is `Ok`.
+#[cfg_attr(not(feature = "metrics"), allow(clippy::extra_unused_type_parameters))]
+pub(crate) fn timed<T: ?Sized>(validate: impl FnOnce() -> Accumulator) -> crate::Result {
+    #[cfg(feature = "metrics")]
+    let started = Instant::now();
+    let accum = validate();
+    #[cfg(feature = "metrics")]
+    return record(core::any::type_name::<T>(), started, accum);
+    #[cfg(not(feature = "metrics"))]
+    accum.into()
+}
+
+/// Like [timed], for asynchronous validation.
+#[cfg_attr(not(feature = "metrics"), allow(clippy::extra_unused_type_parameters))]
+pub(crate) async fn timed_async<T: ?Sized>(
+    validate: impl core::future::Future<Output = Accumulator>,
+) -> crate::Result {
+    #[cfg(feature = "metrics")]
+    let started = Instant::now();
+    let accum = validate.await;
+    #[cfg(feature = "metrics")]
+    return record(core::any::type_name::<T>(), started, accum);
+    #[cfg(not(feature = "metrics"))]
+    accum.into()
+}
+
+#[cfg(feature = "metrics")]
+fn record(type_name: &'static str, started: Instant, accum: Accumulator) -> crate::Result {
+    metrics::histogram!("validatrix_validation_duration_seconds", "type" => type_name)
+        .record(started.elapsed().as_secs_f64());
+    for failure in accum.sink().iter() {
+        // dynamic names could give unbounded label cardinality
+        let key = match failure.keys().first() {
+            None => "$",
+            Some(Key::Field(name)) => name.as_static().unwrap_or("*"),
+            Some(Key::Index(_) | Key::MapKey(_)) => "[]",
+        };
+        let severity = match failure.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        metrics::counter!(
+            "validatrix_failures_total",
+            "type" => type_name,
+            "severity" => severity,
+            "code" => failure.code().unwrap_or_default().to_string(),
+            "key" => key,
+        )
+        .increment(1);
+    }
+    let result = crate::Result::from(accum);
+    let outcome = if result.is_ok() { "valid" } else { "invalid" };
+    metrics::counter!("validatrix_validations_total", "type" => type_name, "outcome" => outcome)
+        .increment(1);
+    result
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{atomic::Ordering, Arc, Mutex},
+    };
+
+    use metrics::{
+        Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+    };
+
+    use crate::{Accumulator, FailureSink, Validate};
+
+    #[derive(Default)]
+    struct Counts(Mutex<BTreeMap<String, Arc<std::sync::atomic::AtomicU64>>>);
+
+    struct TestRecorder(Arc<Counts>);
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            let labels: Vec<_> = key
+                .labels()
+                .filter(|l| l.key() != "type")
+                .map(|l| format!("{}={}", l.key(), l.value()))
+                .collect();
+            let name = format!("{}{{{}}}", key.name(), labels.join(","));
+            let counter = Arc::clone(self.0 .0.lock().unwrap().entry(name).or_default());
+            Counter::from_arc(counter)
+        }
+
+        fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    struct Form;
+
+    impl Validate for Form {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            accum.add_failure_coded_at("name", "required", "is required");
+            accum.with_key("tags", |a| a.add_warning_at(0, "unusual"));
+            accum.add_failure_at(String::from("extra"), "unexpected");
+        }
+    }
+
+    struct Iffy;
+
+    impl Validate for Iffy {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+            accum.add_warning("odd");
+        }
+    }
+
+    #[test]
+    fn records() {
+        let counts = Arc::new(Counts::default());
+        let recorder = TestRecorder(Arc::clone(&counts));
+        metrics::with_local_recorder(&recorder, || {
+            Form.validate().unwrap_err();
+            Form.validate().unwrap_err();
+            None::<Form>.validate().unwrap();
+            Iffy.validate().unwrap();
+        });
+        let counts: Vec<_> = counts
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(k, v)| format!("{k} {}", v.load(Ordering::Relaxed)))
+            .collect();
+        assert_eq!(
+            counts,
+            [
+                "validatrix_failures_total{severity=error,code=,key=*} 2",
+                "validatrix_failures_total{severity=error,code=required,key=name} 2",
+                "validatrix_failures_total{severity=warning,code=,key=$} 1",
+                "validatrix_failures_total{severity=warning,code=,key=tags} 2",
+                "validatrix_validations_total{outcome=invalid} 2",
+                "validatrix_validations_total{outcome=valid} 2",
+            ]
+        );
+    }
+}
@@ -1,14 +1,106 @@
-use std::{borrow::Borrow, ops::Deref};
+use alloc::{sync::Arc, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
-use crate::{Validate, ValidateContext};
+use crate::{Validate, ValidateContext, ValidateProfile};
 
 /// Wrapper type containing a value which must have been validated.
-#[derive(Debug)]
-pub struct Valid<T>(T);
+///
+/// Comparison, hashing and formatting traits are passed through to the inner value,
+/// so e.g. `Valid<String>` can be used as a map key and looked up by `&String`.
+///
+/// The profile `P` records which rules the value was validated against:
+/// `()` for [Validate], or a marker type for [ValidateProfile].
+///
+/// ```
+/// use validatrix::{Accumulator, FailureSink, Valid, ValidateProfile};
+///
+/// struct Create;
+/// struct Update;
+///
+/// struct User {
+///     id: Option<u64>,
+/// }
+///
+/// impl ValidateProfile<Create> for User {
+///     fn validate_inner_profile<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.id.is_some() {
+///             accum.add_failure_at("id", "must not be given");
+///         }
+///     }
+/// }
+///
+/// impl ValidateProfile<Update> for User {
+///     fn validate_inner_profile<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.id.is_none() {
+///             accum.add_failure_at("id", "is required");
+///         }
+///     }
+/// }
+///
+/// fn update(user: Valid<User, Update>) -> u64 {
+///     user.id.unwrap()
+/// }
+///
+/// let user = Valid::<_, Update>::try_new_profile(User { id: Some(1) }).unwrap();
+/// assert_eq!(update(user), 1);
+/// assert!(Valid::<_, Create>::try_new_profile(User { id: Some(1) }).is_err());
+/// ```
+pub struct Valid<T, P = ()>(T, PhantomData<fn() -> P>);
+
+impl<T: core::fmt::Debug, P> core::fmt::Debug for Valid<T, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Valid").field(&self.0).finish()
+    }
+}
+
+// Manually implemented so that the profile does not need to implement these traits
+impl<T: Clone, P> Clone for Valid<T, P> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<T: PartialEq, P> PartialEq for Valid<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: Eq, P> Eq for Valid<T, P> {}
+
+impl<T: Hash, P> Hash for Valid<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: PartialOrd, P> PartialOrd for Valid<T, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<T: Ord, P> Ord for Valid<T, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<T: core::fmt::Display, P> core::fmt::Display for Valid<T, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
 
 // Manually implemented to save depending on the derive feature of serde
 #[cfg(feature = "serde")]
-impl<T: serde::Serialize> serde::Serialize for Valid<T> {
+impl<T: serde::Serialize, P> serde::Serialize for Valid<T, P> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -18,6 +110,34 @@ impl<T: serde::Serialize> serde::Serialize for Valid<T> {
     }
 }
 
+/// Deserializes `T` directly, then validates it,
+/// so borrowed data is supported as well as it is by `T`.
+///
+/// `deserialize_in_place` is not forwarded to `T`,
+/// as a value which fails validation would be left in the wrapper.
+///
+/// Failures do not have [spans](crate::span), as deserializers do not expose the source text.
+/// To locate them, deserialize `T` itself, then validate it with [Valid::try_new]
+/// and call [Error::locate](crate::Error::locate) with the text.
+///
+/// ```
+/// use validatrix::{Accumulator, FailureSink, Valid, Validate};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Name<'a>(&'a str);
+///
+/// impl Validate for Name<'_> {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if self.0.is_empty() {
+///             accum.add_failure("must not be empty");
+///         }
+///     }
+/// }
+///
+/// let json = String::from(r#""borrowed""#);
+/// let name: Valid<Name> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(name.0.as_ptr(), json[1..].as_ptr());
+/// ```
 #[cfg(feature = "serde")]
 impl<'de, T: serde::de::Deserialize<'de> + Validate> serde::de::Deserialize<'de> for Valid<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -29,7 +149,104 @@ impl<'de, T: serde::de::Deserialize<'de> + Validate> serde::de::Deserialize<'de>
     }
 }
 
+/// Uses the schema of the inner type: validation rules are not represented in the schema.
+#[cfg(feature = "schemars")]
+impl<T: schemars::JsonSchema, P> schemars::JsonSchema for Valid<T, P> {
+    fn inline_schema() -> bool {
+        T::inline_schema()
+    }
+
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        T::schema_name()
+    }
+
+    fn schema_id() -> alloc::borrow::Cow<'static, str> {
+        T::schema_id()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        T::json_schema(generator)
+    }
+}
+
+/// Deserializes a [Valid] value, validating it with the given context.
+///
+/// ```
+/// use serde::de::DeserializeSeed;
+/// use validatrix::{Accumulator, FailureSink, ValidSeed, ValidateContext};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Upload {
+///     size: u64,
+/// }
+///
+/// struct Tenant {
+///     max_size: u64,
+/// }
+///
+/// impl ValidateContext for Upload {
+///     type Context = Tenant;
+///
+///     fn validate_inner_ctx<S: FailureSink>(&self, accum: &mut Accumulator<S>, context: &Tenant) {
+///         if self.size > context.max_size {
+///             accum.add_failure_at("size", "too large");
+///         }
+///     }
+/// }
+///
+/// let tenant = Tenant { max_size: 100 };
+/// let mut de = serde_json::Deserializer::from_str(r#"{"size": 1000}"#);
+/// let err = ValidSeed::<Upload>::new(&tenant).deserialize(&mut de).err().unwrap();
+/// assert!(err.to_string().contains("$.size: too large"));
+/// ```
+#[cfg(feature = "serde")]
+pub struct ValidSeed<'a, T: ValidateContext> {
+    context: &'a T::Context,
+    _marker: core::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T: ValidateContext> ValidSeed<'a, T> {
+    /// Create a seed which validates with the given context.
+    pub fn new(context: &'a T::Context) -> Self {
+        Self {
+            context,
+            _marker: core::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::de::DeserializeSeed<'de> for ValidSeed<'_, T>
+where
+    T: serde::de::Deserialize<'de> + ValidateContext,
+{
+    type Value = Valid<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = T::deserialize(deserializer)?;
+        Valid::try_new_ctx(value, self.context).map_err(serde::de::Error::custom)
+    }
+}
+
 impl<T> Valid<T> {
+    /// Wrap a value without validating it.
+    ///
+    /// Only use this for values known to be valid, e.g. because they were validated
+    /// before being written to a trusted store, to skip the cost of revalidating them.
+    ///
+    /// This is not `unsafe`, as no memory safety depends on validity,
+    /// but code receiving a `Valid<T>` will assume the value passes [Validate::validate].
+    /// Debug builds check this assumption if `T` implements [Validate]; see [Valid::assume_valid].
+    pub fn new_unchecked(inner: T) -> Self {
+        Self(inner, PhantomData)
+    }
+}
+
+impl<T, P> Valid<T, P> {
     /// Borrow a reference to the contained valid value.
     pub fn inner(&self) -> &T {
         &self.0
@@ -39,9 +256,140 @@ impl<T> Valid<T> {
     pub fn into_inner(self) -> T {
         self.0
     }
+
+    /// Borrow the contained value as a [ValidRef].
+    pub fn as_valid_ref(&self) -> ValidRef<'_, T, P> {
+        ValidRef(&self.0, PhantomData)
+    }
+
+    /// Borrow part of the contained value as a [ValidRef], without revalidating it.
+    ///
+    /// The closure must return a part which was validated as part of the whole,
+    /// e.g. a field whose validator is called by the containing type's validator.
+    /// This is not checked.
+    ///
+    /// The profile `P` is carried over to the part,
+    /// as the part was checked by the whole's validator for that profile.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Valid, ValidRef, Validate};
+    /// struct Host(String);
+    ///
+    /// impl Validate for Host {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0.is_empty() {
+    ///             accum.add_failure("must not be empty");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// struct Server {
+    ///     host: Host,
+    /// }
+    ///
+    /// impl Validate for Server {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         accum.validate_member_at("host", &self.host);
+    ///     }
+    /// }
+    ///
+    /// fn connect(host: ValidRef<Host>) -> &str {
+    ///     &host.into_inner().0
+    /// }
+    ///
+    /// let server = Valid::try_new(Server { host: Host("localhost".into()) }).unwrap();
+    /// assert_eq!(connect(server.project(|s| &s.host)), "localhost");
+    /// ```
+    pub fn project<U: ?Sized>(&self, f: impl FnOnce(&T) -> &U) -> ValidRef<'_, U, P> {
+        ValidRef(f(&self.0), PhantomData)
+    }
+
+    /// Transform the contained value, validating the result.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Valid, Validate};
+    /// struct Port(u16);
+    ///
+    /// impl Validate for Port {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0 < 1024 {
+    ///             accum.add_failure("must not be privileged");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let port = Valid::try_new(Port(8080)).unwrap();
+    /// assert!(port.try_map(|p| Port(p.0 - 8000)).is_err());
+    /// ```
+    pub fn try_map<U: Validate>(self, f: impl FnOnce(T) -> U) -> crate::Result<Valid<U>> {
+        Valid::try_new(f(self.0))
+    }
+
+    /// Transform the contained value without validating the result.
+    ///
+    /// Only use this for transformations which cannot make a valid value invalid;
+    /// see [Valid::new_unchecked].
+    pub fn map_unchecked<U>(self, f: impl FnOnce(T) -> U) -> Valid<U, P> {
+        Valid(f(self.0), PhantomData)
+    }
+
+    /// Move the contained value into an [Arc], so that it can be cheaply shared
+    /// (e.g. as application state) while remaining wrapped.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use validatrix::Valid;
+    /// let config: Valid<Arc<Vec<u8>>> = Valid::new_unchecked(vec![1]).into_shared();
+    /// let shared = config.clone();
+    /// assert!(Arc::ptr_eq(&config, &shared));
+    /// let owned: Valid<Vec<u8>> = shared.clone_inner();
+    /// assert_eq!(*owned, [1]);
+    /// ```
+    pub fn into_shared(self) -> Valid<Arc<T>, P> {
+        Valid(Arc::new(self.0), PhantomData)
+    }
+}
+
+impl<T: Clone, P> Valid<Arc<T>, P> {
+    /// Clone the shared value into an owned [Valid], without revalidating it.
+    pub fn clone_inner(&self) -> Valid<T, P> {
+        Valid(T::clone(&self.0), PhantomData)
+    }
+
+    /// Unwrap the shared value if there are no other references to it,
+    /// or clone it otherwise, without revalidating it.
+    pub fn unwrap_or_clone(self) -> Valid<T, P> {
+        Valid(
+            Arc::try_unwrap(self.0).unwrap_or_else(|arc| T::clone(&arc)),
+            PhantomData,
+        )
+    }
+}
+
+impl<T> Valid<Vec<T>> {
+    /// Borrow the validated elements as a slice.
+    ///
+    /// ```
+    /// # use std::num::NonZeroU16;
+    /// # use validatrix::{Valid, ValidRef};
+    /// let ports = vec![NonZeroU16::new(80).unwrap(), NonZeroU16::new(443).unwrap()];
+    /// let ports = Valid::try_new(ports).unwrap();
+    /// let slice: ValidRef<[NonZeroU16]> = ports.as_valid_slice();
+    /// let first: ValidRef<NonZeroU16> = slice.get(0).unwrap();
+    /// assert_eq!(first.get(), 80);
+    /// assert_eq!(slice.iter().count(), 2);
+    /// ```
+    pub fn as_valid_slice(&self) -> ValidRef<'_, [T]> {
+        ValidRef(self.0.as_slice(), PhantomData)
+    }
+
+    /// Iterate over the validated elements, each of which was validated as part of the whole.
+    pub fn into_valid_iter(self) -> impl Iterator<Item = Valid<T>> {
+        self.0.into_iter().map(|item| Valid(item, PhantomData))
+    }
 }
 
-impl<T> Deref for Valid<T> {
+impl<T, P> Deref for Valid<T, P> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -49,46 +397,391 @@ impl<T> Deref for Valid<T> {
     }
 }
 
-impl<T> AsRef<T> for Valid<T> {
+impl<T, P> AsRef<T> for Valid<T, P> {
     fn as_ref(&self) -> &T {
         &self.0
     }
 }
 
-impl<T> Borrow<T> for Valid<T> {
+impl<T, P> Borrow<T> for Valid<T, P> {
     fn borrow(&self) -> &T {
         &self.0
     }
 }
 
+/// Reference to a value which must have been validated;
+/// the borrowed counterpart of [Valid].
+///
+/// ```
+/// use validatrix::{Accumulator, FailureSink, Valid, ValidRef, Validate};
+///
+/// struct Email(String);
+///
+/// impl Validate for Email {
+///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+///         if !self.0.contains('@') {
+///             accum.add_failure("must contain '@'");
+///         }
+///     }
+/// }
+///
+/// fn send(to: ValidRef<Email>) -> usize {
+///     to.0.len()
+/// }
+///
+/// let email = Email("a@b.c".into());
+/// assert_eq!(send(ValidRef::try_new(&email).unwrap()), 5);
+/// let email = Valid::try_new(email).unwrap();
+/// assert_eq!(send(email.as_valid_ref()), 5);
+/// ```
+pub struct ValidRef<'a, T: ?Sized, P = ()>(&'a T, PhantomData<fn() -> P>);
+
+impl<'a, T: ?Sized> ValidRef<'a, T> {
+    /// Wrap a reference without validating the value; see [Valid::new_unchecked].
+    pub fn new_unchecked(inner: &'a T) -> Self {
+        Self(inner, PhantomData)
+    }
+}
+
+impl<'a, T: ?Sized + Validate> ValidRef<'a, T> {
+    /// Validate the referenced value and return the wrapped reference.
+    pub fn try_new(inner: &'a T) -> crate::Result<Self> {
+        inner.validate()?;
+        Ok(Self(inner, PhantomData))
+    }
+}
+
+impl<'a, T: ?Sized + ValidateProfile<P>, P> ValidRef<'a, T, P> {
+    /// Validate the referenced value against the profile `P` and return the wrapped reference.
+    pub fn try_new_profile(inner: &'a T) -> crate::Result<Self> {
+        inner.validate_profile()?;
+        Ok(Self(inner, PhantomData))
+    }
+}
+
+impl<'a, T: ?Sized, P> ValidRef<'a, T, P> {
+    /// Unwrap into the contained reference.
+    pub fn into_inner(self) -> &'a T {
+        self.0
+    }
+
+    /// Borrow part of the referenced value, without revalidating it; see [Valid::project].
+    pub fn project<U: ?Sized>(self, f: impl FnOnce(&'a T) -> &'a U) -> ValidRef<'a, U, P> {
+        ValidRef(f(self.0), PhantomData)
+    }
+}
+
+impl<'a, T, P> ValidRef<'a, [T], P> {
+    /// Get a validated element, if the index is in bounds.
+    pub fn get(self, index: usize) -> Option<ValidRef<'a, T, P>> {
+        self.0.get(index).map(|v| ValidRef(v, PhantomData))
+    }
+
+    /// Iterate over the validated elements.
+    pub fn iter(self) -> impl Iterator<Item = ValidRef<'a, T, P>> {
+        self.0.iter().map(|v| ValidRef(v, PhantomData))
+    }
+}
+
+impl<T: Clone, P> ValidRef<'_, T, P> {
+    /// Clone the referenced value into an owned [Valid], without revalidating it.
+    pub fn cloned(self) -> Valid<T, P> {
+        Valid(self.0.clone(), PhantomData)
+    }
+}
+
+impl<T: ?Sized, P> Clone for ValidRef<'_, T, P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized, P> Copy for ValidRef<'_, T, P> {}
+
+impl<T: ?Sized + core::fmt::Debug, P> core::fmt::Debug for ValidRef<'_, T, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ValidRef").field(&self.0).finish()
+    }
+}
+
+impl<T: ?Sized + core::fmt::Display, P> core::fmt::Display for ValidRef<'_, T, P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, P> PartialEq for ValidRef<'_, T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: ?Sized + Eq, P> Eq for ValidRef<'_, T, P> {}
+
+impl<T: ?Sized + Hash, P> Hash for ValidRef<'_, T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: ?Sized, P> Deref for ValidRef<'_, T, P> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<T: ?Sized, P> AsRef<T> for ValidRef<'_, T, P> {
+    fn as_ref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<'a, T, P> From<&'a Valid<T, P>> for ValidRef<'a, T, P> {
+    fn from(value: &'a Valid<T, P>) -> Self {
+        value.as_valid_ref()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + serde::Serialize, P> serde::Serialize for ValidRef<'_, T, P> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
 // N.B. can't use TryFrom because of the blanket implementation of TryFrom for From;
-// too generic.
+// too generic. See impl_try_from_valid for concrete types.
 impl<T: Validate> Valid<T> {
     /// Validate the inner value and return the wrapped form.
     pub fn try_new(inner: T) -> crate::Result<Self> {
         inner.validate()?;
-        Ok(Self(inner))
+        Ok(Self(inner, PhantomData))
+    }
+
+    /// Wrap a value without validating it, like [Valid::new_unchecked],
+    /// but validating it anyway in debug builds.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if the value is invalid.
+    pub fn assume_valid(inner: T) -> Self {
+        if cfg!(debug_assertions) {
+            if let Err(e) = inner.validate() {
+                panic!("value assumed to be valid is not: {e}");
+            }
+        }
+        Self(inner, PhantomData)
+    }
+}
+
+impl<T: ValidateProfile<P>, P> Valid<T, P> {
+    /// Validate the inner value against the profile `P` and return the wrapped form.
+    pub fn try_new_profile(inner: T) -> crate::Result<Self> {
+        inner.validate_profile()?;
+        Ok(Self(inner, PhantomData))
+    }
+}
+
+impl<T: Validate + Clone> Valid<T> {
+    /// Mutably borrow the contained value through a guard which revalidates it.
+    ///
+    /// Call [ValidMut::commit] to find out whether the modified value is valid.
+    /// If it is not, or if the guard is dropped without committing and the value is not valid,
+    /// the original value is restored.
+    /// The original is cloned to make this possible.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Valid, Validate};
+    /// #[derive(Debug, Clone)]
+    /// struct Range {
+    ///     min: u8,
+    ///     max: u8,
+    /// }
+    ///
+    /// impl Validate for Range {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.min > self.max {
+    ///             accum.add_failure("min must not exceed max");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut range = Valid::try_new(Range { min: 1, max: 5 }).unwrap();
+    ///
+    /// let mut guard = range.modify();
+    /// guard.min = 10;
+    /// assert!(guard.commit().is_err());
+    /// assert_eq!(range.min, 1);
+    ///
+    /// let mut guard = range.modify();
+    /// guard.min = 2;
+    /// guard.commit().unwrap();
+    /// assert_eq!(range.min, 2);
+    /// ```
+    pub fn modify(&mut self) -> ValidMut<'_, T> {
+        ValidMut {
+            original: Some(self.0.clone()),
+            valid: self,
+        }
+    }
+
+    /// Modify the contained value with a closure, keeping the changes only if the result is valid.
+    ///
+    /// With the `std` feature, the original value is restored if the closure panics,
+    /// even if the partly-modified value happens to be valid.
+    ///
+    /// ```
+    /// # use validatrix::{Accumulator, FailureSink, Valid, Validate};
+    /// #[derive(Debug, Clone)]
+    /// struct Tags(Vec<String>);
+    ///
+    /// impl Validate for Tags {
+    ///     fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         if self.0.len() > 2 {
+    ///             accum.add_failure("too many tags");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut tags = Valid::try_new(Tags(vec!["a".into()])).unwrap();
+    /// tags.update(|t| t.0.push("b".into())).unwrap();
+    /// assert!(tags.update(|t| t.0.push("c".into())).is_err());
+    /// assert_eq!(tags.0, ["a", "b"]);
+    /// ```
+    pub fn update(&mut self, f: impl FnOnce(&mut T)) -> crate::Result<()> {
+        let mut guard = self.modify();
+        f(&mut guard);
+        guard.commit()
+    }
+}
+
+/// Guard allowing mutation of the value in a [Valid], created by [Valid::modify].
+#[derive(Debug)]
+pub struct ValidMut<'a, T: Validate> {
+    valid: &'a mut Valid<T>,
+    /// `None` once committed.
+    original: Option<T>,
+}
+
+impl<T: Validate> ValidMut<'_, T> {
+    /// Validate the modified value, restoring the original if it is not valid.
+    pub fn commit(mut self) -> crate::Result<()> {
+        self.revalidate()
+    }
+
+    /// Discard the modifications, restoring the original value.
+    pub fn rollback(mut self) {
+        self.restore();
+    }
+
+    fn restore(&mut self) {
+        if let Some(original) = self.original.take() {
+            self.valid.0 = original;
+        }
+    }
+
+    fn revalidate(&mut self) -> crate::Result<()> {
+        let Some(original) = self.original.take() else {
+            return Ok(());
+        };
+        let result = self.valid.0.validate();
+        if result.is_err() {
+            self.valid.0 = original;
+        }
+        result
+    }
+}
+
+impl<T: Validate> Deref for ValidMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.valid.0
+    }
+}
+
+impl<T: Validate> DerefMut for ValidMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.valid.0
+    }
+}
+
+/// Revalidates the modified value if it has not been committed,
+/// restoring the original if it is not valid.
+///
+/// If the guard is dropped while the thread is panicking,
+/// the original is restored without revalidating, as the modification may be incomplete.
+/// This needs the `std` feature; without it, the modified value is revalidated as usual.
+impl<T: Validate> Drop for ValidMut<'_, T> {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        if std::thread::panicking() {
+            self.restore();
+            return;
+        }
+        let _ = self.revalidate();
     }
 }
 
 impl<T: ValidateContext> Valid<T> {
     pub fn try_new_ctx(inner: T, context: &T::Context) -> crate::Result<Self> {
         inner.validate_ctx(context)?;
-        Ok(Self(inner))
+        Ok(Self(inner, PhantomData))
+    }
+}
+
+impl<T: crate::asynch::Validate> Valid<T> {
+    /// Validate the inner value asynchronously and return the wrapped form.
+    ///
+    /// ```
+    /// use validatrix::{asynch::Validate, Accumulator, FailureSink, Valid};
+    ///
+    /// struct Username(String);
+    ///
+    /// impl Validate for Username {
+    ///     async fn validate_inner<S: FailureSink>(&self, accum: &mut Accumulator<S>) {
+    ///         // e.g. look up the name in a database
+    ///         if self.0 == "admin" {
+    ///             accum.add_failure("is taken");
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// pollster::block_on(async {
+    ///     assert!(Valid::try_new_async(Username("alice".into())).await.is_ok());
+    ///     assert!(Valid::try_new_async(Username("admin".into())).await.is_err());
+    /// });
+    /// ```
+    pub async fn try_new_async(inner: T) -> crate::Result<Self> {
+        inner.validate().await?;
+        Ok(Self(inner, PhantomData))
+    }
+}
+
+impl<T: crate::asynch::ValidateContext> Valid<T> {
+    /// Validate the inner value asynchronously with the given context and return the wrapped form.
+    pub async fn try_new_ctx_async(inner: T, context: &T::Context<'_>) -> crate::Result<Self> {
+        inner.validate_ctx(context).await?;
+        Ok(Self(inner, PhantomData))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Valid, Validate};
+    use crate::{FailureSink, Valid, Validate};
 
-    #[derive(Debug, serde::Serialize, serde::Deserialize)]
+    #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
     struct MyStruct {
         is_valid: bool,
     }
 
     impl Validate for MyStruct {
-        fn validate_inner(&self, accum: &mut crate::Accumulator) {
+        fn validate_inner<S: FailureSink>(&self, accum: &mut crate::Accumulator<S>) {
             if !self.is_valid {
                 accum.add_failure_at("is_valid", "struct marked invalid");
             }
@@ -105,6 +798,20 @@ mod tests {
         assert!(Valid::try_new(MyStruct { is_valid: false }).is_err())
     }
 
+    #[test]
+    fn test_unchecked() {
+        let valid = Valid::new_unchecked(MyStruct { is_valid: false });
+        assert!(!valid.is_valid);
+        assert!(Valid::assume_valid(MyStruct { is_valid: true }).is_valid);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic = "value assumed to be valid is not"]
+    fn test_assume_invalid() {
+        Valid::assume_valid(MyStruct { is_valid: false });
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serde_ser() {
@@ -127,4 +834,210 @@ mod tests {
         let s = r#"{"is_valid":false}"#;
         let _valid: Valid<MyStruct> = serde_json::from_str(s).unwrap();
     }
+
+    #[cfg(feature = "serde")]
+    struct Expected(bool);
+
+    #[cfg(feature = "serde")]
+    impl crate::ValidateContext for MyStruct {
+        type Context = Expected;
+
+        fn validate_inner_ctx<S: FailureSink>(
+            &self,
+            accum: &mut crate::Accumulator<S>,
+            context: &Expected,
+        ) {
+            if self.is_valid != context.0 {
+                accum.add_failure("unexpected");
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_seed() {
+        use serde::de::DeserializeSeed;
+
+        let json = r#"{"is_valid":false}"#;
+        let seed = crate::ValidSeed::<MyStruct>::new(&Expected(false));
+        let mut de = serde_json::Deserializer::from_str(json);
+        assert!(!seed.deserialize(&mut de).unwrap().is_valid);
+        let seed = crate::ValidSeed::<MyStruct>::new(&Expected(true));
+        let mut de = serde_json::Deserializer::from_str(json);
+        assert!(seed.deserialize(&mut de).is_err());
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut valid = Valid::try_new(MyStruct { is_valid: true }).unwrap();
+        valid.modify().is_valid = false;
+        assert!(valid.is_valid);
+
+        let mut guard = valid.modify();
+        guard.is_valid = false;
+        guard.is_valid = true;
+        assert!(guard.commit().is_ok());
+
+        let mut guard = valid.modify();
+        guard.is_valid = false;
+        guard.rollback();
+        assert!(valid.is_valid);
+    }
+
+    #[test]
+    fn test_map() {
+        let valid = Valid::try_new(MyStruct { is_valid: true }).unwrap();
+        let valid = valid
+            .try_map(|s| MyStruct {
+                is_valid: s.is_valid,
+            })
+            .unwrap();
+        let flag = valid.map_unchecked(|s| s.is_valid);
+        assert!(*flag);
+        let valid = Valid::try_new(MyStruct { is_valid: true }).unwrap();
+        assert!(valid.try_map(|_| MyStruct { is_valid: false }).is_err());
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let a = Valid::new_unchecked("a".to_string());
+        let b = a.clone();
+        assert_eq!(a, b);
+        assert!(a < Valid::new_unchecked("b".to_string()));
+        assert_eq!(format!("{a:>3}"), "  a");
+        let set: std::collections::HashSet<_> = [a].into_iter().collect();
+        assert!(set.contains(&b));
+    }
+
+    #[test]
+    fn test_async() {
+        struct Limit(u8);
+
+        impl crate::asynch::ValidateContext for Limit {
+            type Context<'c> = u8;
+
+            async fn validate_inner_ctx<S: FailureSink>(
+                &self,
+                context: &Self::Context<'_>,
+                accum: &mut crate::Accumulator<S>,
+            ) {
+                if self.0 > *context {
+                    accum.add_failure("over the limit");
+                }
+            }
+        }
+
+        pollster::block_on(async {
+            assert!(Valid::try_new_ctx_async(Limit(1), &2).await.is_ok());
+            assert!(Valid::try_new_ctx_async(Limit(3), &2).await.is_err());
+        });
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_schemars() {
+        assert_eq!(
+            schemars::schema_for!(Valid<Vec<u8>>),
+            schemars::schema_for!(Vec<u8>)
+        );
+    }
+
+    #[test]
+    fn test_update_panic() {
+        let mut valid = Valid::try_new(MyStruct { is_valid: true }).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            valid
+                .update(|s| {
+                    s.is_valid = false;
+                    panic!("oops");
+                })
+                .ok()
+        }));
+        assert!(result.is_err());
+        assert!(valid.is_valid);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_update_panic_restores() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct Pair(u8, u8);
+
+        impl Validate for Pair {
+            fn validate_inner<S: FailureSink>(&self, accum: &mut crate::Accumulator<S>) {
+                if self.0 != self.1 {
+                    accum.add_failure("unequal");
+                }
+            }
+        }
+
+        let mut pair = Valid::try_new(Pair(1, 1)).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            pair.update(|p| {
+                p.0 = 2;
+                p.1 = 2;
+                panic!("oops");
+            })
+        }));
+        assert!(result.is_err());
+        assert_eq!(*pair, Pair(1, 1));
+    }
+
+    #[test]
+    fn test_valid_ref() {
+        use crate::ValidRef;
+
+        let value = MyStruct { is_valid: false };
+        assert!(ValidRef::try_new(&value).is_err());
+        let valid = Valid::try_new(MyStruct { is_valid: true }).unwrap();
+        let r = ValidRef::from(&valid);
+        let copy = r;
+        assert!(copy.is_valid && r.is_valid);
+        assert!(r.cloned().is_valid);
+    }
+
+    #[test]
+    fn test_project() {
+        let valid = Valid::try_new(vec![MyStruct { is_valid: true }]).unwrap();
+        let first = valid.project(|v| &v[0]);
+        assert!(first.project(|s| &s.is_valid).into_inner());
+    }
+
+    #[test]
+    fn test_project_profile() {
+        struct Strict;
+        struct Items(Vec<MyStruct>);
+
+        impl crate::ValidateProfile<Strict> for Items {
+            fn validate_inner_profile<S: FailureSink>(&self, accum: &mut crate::Accumulator<S>) {
+                accum.validate_iter_at("0", &self.0);
+            }
+        }
+
+        let valid =
+            Valid::<_, Strict>::try_new_profile(Items(vec![MyStruct { is_valid: true }])).unwrap();
+        let items: crate::ValidRef<'_, [MyStruct], Strict> = valid.project(|p| p.0.as_slice());
+        let first: crate::ValidRef<'_, MyStruct, Strict> = items.get(0).unwrap();
+        assert!(first.is_valid);
+    }
+
+    #[test]
+    fn test_shared() {
+        let valid = Valid::try_new(MyStruct { is_valid: true }).unwrap();
+        let shared = valid.into_shared();
+        let other = shared.clone();
+        assert!(other.validate().is_ok());
+        assert!(shared.unwrap_or_clone().is_valid);
+        assert!(other.unwrap_or_clone().is_valid);
+    }
+
+    #[test]
+    fn test_collections() {
+        let valid = Valid::try_new(vec![MyStruct { is_valid: true }]).unwrap();
+        assert!(valid.as_valid_slice().get(1).is_none());
+        assert!(valid.as_valid_slice().iter().all(|s| s.is_valid));
+        let items: Vec<Valid<MyStruct>> = valid.into_valid_iter().collect();
+        assert_eq!(items.len(), 1);
+        assert!(Valid::try_new(vec![MyStruct { is_valid: false }]).is_err());
+    }
 }